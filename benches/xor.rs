@@ -0,0 +1,129 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use cryptopals::xor::kernel;
+
+/// Input sizes to benchmark, in bytes
+const SIZES: [usize; 3] = [64, 4096, 1 << 20];
+
+/// Deterministic pseudo-random input data
+fn data(len: usize, seed: u32) -> Vec<u8> {
+    (0..len as u32)
+        .map(|i| (i.wrapping_add(seed).wrapping_mul(2_654_435_761) >> 24) as u8)
+        .collect()
+}
+
+/// Byte-by-byte xor, as previously done by `static_xor`
+fn bytewise_xor(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b).map(|(x, y)| x ^ y).collect()
+}
+
+/// Byte-by-byte repeating-key xor, as previously done by `repeating_key_xor`
+fn bytewise_repeating_key_xor(key: &[u8], s: &[u8]) -> Vec<u8> {
+    s.iter()
+        .enumerate()
+        .map(|(i, c)| c ^ key[i % key.len()])
+        .collect()
+}
+
+/// Byte-by-byte Hamming distance, as previously done by `hamming_distance`
+fn bytewise_hamming_distance(a: &[u8], b: &[u8]) -> usize {
+    let mut distance = 0;
+    for (byte_a, byte_b) in a.iter().zip(b) {
+        distance += (byte_a ^ byte_b).count_ones() as usize;
+    }
+    distance
+}
+
+fn bench_xor(c: &mut Criterion) {
+    let mut group = c.benchmark_group("xor");
+    for size in SIZES {
+        let (a, b) = (data(size, 1), data(size, 2));
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("bytewise", size), &size, |bench, _| {
+            bench.iter(|| bytewise_xor(black_box(&a), black_box(&b)))
+        });
+        group.bench_with_input(BenchmarkId::new("kernel", size), &size, |bench, _| {
+            bench.iter(|| {
+                let mut buf = black_box(&a).clone();
+                kernel::xor_in_place(&mut buf, black_box(&b));
+                buf
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_repeating_key_xor(c: &mut Criterion) {
+    let mut group = c.benchmark_group("repeating_key_xor");
+    let key = data(29, 3);
+    for size in SIZES {
+        let input = data(size, 4);
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("bytewise", size), &size, |bench, _| {
+            bench.iter(|| bytewise_repeating_key_xor(black_box(&key), black_box(&input)))
+        });
+        group.bench_with_input(BenchmarkId::new("kernel", size), &size, |bench, _| {
+            bench.iter(|| {
+                let mut buf = black_box(&input).clone();
+                kernel::xor_repeating_in_place(&mut buf, black_box(&key));
+                buf
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_hamming_distance(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hamming_distance");
+    for size in SIZES {
+        let (a, b) = (data(size, 5), data(size, 6));
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("bytewise", size), &size, |bench, _| {
+            bench.iter(|| bytewise_hamming_distance(black_box(&a), black_box(&b)))
+        });
+        group.bench_with_input(BenchmarkId::new("kernel", size), &size, |bench, _| {
+            bench.iter(|| kernel::xor_popcount(black_box(&a), black_box(&b)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_single_byte_brute_force(c: &mut Criterion) {
+    let mut group = c.benchmark_group("single_byte_brute_force");
+    let input = data(1 << 16, 7);
+    group.throughput(Throughput::Bytes(256 * input.len() as u64));
+    group.bench_function("bytewise", |bench| {
+        bench.iter(|| {
+            (0..=u8::MAX)
+                .map(|key| {
+                    black_box(&input)
+                        .iter()
+                        .map(|b| b ^ key)
+                        .collect::<Vec<_>>()
+                })
+                .map(|plaintext| plaintext[0])
+                .fold(0u8, |acc, b| acc ^ b)
+        })
+    });
+    group.bench_function("kernel", |bench| {
+        let mut buf = input.clone();
+        bench.iter(|| {
+            (0..=u8::MAX)
+                .map(|key| {
+                    buf.copy_from_slice(black_box(&input));
+                    kernel::xor_byte_in_place(&mut buf, key);
+                    buf[0]
+                })
+                .fold(0u8, |acc, b| acc ^ b)
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_xor,
+    bench_repeating_key_xor,
+    bench_hamming_distance,
+    bench_single_byte_brute_force
+);
+criterion_main!(benches);
@@ -0,0 +1,2 @@
+pub mod bitflip;
+pub mod byte_at_a_time;
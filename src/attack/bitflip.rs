@@ -0,0 +1,392 @@
+use std::{error::Error, fmt};
+
+use crate::xor::{xor, xor_into};
+
+/// Error produced when flipping ciphertext bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitflipError {
+    /// The known and desired plaintexts differ in length
+    LengthMismatch { known: usize, desired: usize },
+    /// The target starting at the offset doesn't fit in the ciphertext
+    OutOfRange { offset: usize, len: usize },
+    /// The target lies in the first CBC block, which is only controlled by the IV
+    NoPrecedingBlock { offset: usize },
+    /// The target crosses a CBC block boundary
+    SpansBlocks { offset: usize, len: usize },
+    /// CBC blocks can't be empty
+    InvalidBlockSize,
+    /// Our input never changed the ciphertext
+    PrefixNotFound,
+}
+
+impl fmt::Display for BitflipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BitflipError::LengthMismatch { known, desired } => write!(
+                f,
+                "known plaintext of length {} can't be flipped to length {}",
+                known, desired
+            ),
+            BitflipError::OutOfRange { offset, len } => {
+                write!(f, "offset {} out of range for length {}", offset, len)
+            }
+            BitflipError::NoPrecedingBlock { offset } => {
+                write!(f, "no ciphertext block precedes offset {}", offset)
+            }
+            BitflipError::SpansBlocks { offset, len } => write!(
+                f,
+                "{} bytes at offset {} cross a block boundary",
+                len, offset
+            ),
+            BitflipError::InvalidBlockSize => write!(f, "block size must not be zero"),
+            BitflipError::PrefixNotFound => write!(f, "prefix length not found"),
+        }
+    }
+}
+
+impl Error for BitflipError {}
+
+/// Mode of the cipher encrypting the oracle output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlipMode {
+    /// Flipping a ciphertext bit flips the same bit of the next plaintext block
+    Cbc { block_size: usize },
+    /// Flipping a ciphertext bit flips the same plaintext bit
+    Ctr,
+}
+
+impl FlipMode {
+    /// Granularity in which our input affects the ciphertext
+    fn unit(&self) -> usize {
+        match self {
+            FlipMode::Cbc { block_size } => *block_size,
+            FlipMode::Ctr => 1,
+        }
+    }
+}
+
+/// The xor delta turning the known plaintext into the desired plaintext
+pub fn xor_delta<K, D>(known: K, desired: D) -> Result<Vec<u8>, BitflipError>
+where
+    K: AsRef<[u8]>,
+    D: AsRef<[u8]>,
+{
+    let (known, desired) = (known.as_ref(), desired.as_ref());
+    xor(known, desired).map_err(|_| BitflipError::LengthMismatch {
+        known: known.len(),
+        desired: desired.len(),
+    })
+}
+
+/// Flip the CTR ciphertext bits turning the known plaintext at the offset into the desired plaintext
+pub fn flip_ctr<K, D>(
+    ciphertext: &mut [u8],
+    offset: usize,
+    known: K,
+    desired: D,
+) -> Result<(), BitflipError>
+where
+    K: AsRef<[u8]>,
+    D: AsRef<[u8]>,
+{
+    let delta = xor_delta(known, desired)?;
+    let len = ciphertext.len();
+    let target = offset
+        .checked_add(delta.len())
+        .and_then(|end| ciphertext.get_mut(offset..end))
+        .ok_or(BitflipError::OutOfRange { offset, len })?;
+    // Both slices have the same length
+    xor_into(target, &delta).unwrap();
+    Ok(())
+}
+
+/// Flip the CBC ciphertext bits turning the known plaintext at the offset into the desired plaintext
+///
+/// The bits are flipped in the preceding ciphertext block, which garbles the
+/// plaintext of that block. The target must therefore lie within a single
+/// block other than the first one.
+pub fn flip_cbc<K, D>(
+    ciphertext: &mut [u8],
+    block_size: usize,
+    offset: usize,
+    known: K,
+    desired: D,
+) -> Result<(), BitflipError>
+where
+    K: AsRef<[u8]>,
+    D: AsRef<[u8]>,
+{
+    if block_size == 0 {
+        return Err(BitflipError::InvalidBlockSize);
+    }
+    let delta = xor_delta(known, desired)?;
+    let end = offset
+        .checked_add(delta.len())
+        .filter(|&end| end <= ciphertext.len())
+        .ok_or(BitflipError::OutOfRange {
+            offset,
+            len: ciphertext.len(),
+        })?;
+    if !delta.is_empty() && offset / block_size != (end - 1) / block_size {
+        return Err(BitflipError::SpansBlocks {
+            offset,
+            len: delta.len(),
+        });
+    }
+    let offset = offset
+        .checked_sub(block_size)
+        .ok_or(BitflipError::NoPrecedingBlock { offset })?;
+    // Both slices have the same length
+    xor_into(&mut ciphertext[offset..offset + delta.len()], &delta).unwrap();
+    Ok(())
+}
+
+/// Bitflipping attack on an oracle encrypting `prefix || quote(input) || suffix`
+/// using CBC or CTR under a fixed key
+///
+/// The oracle may quote metacharacters in our input, but must leave the filler
+/// byte `A` alone and keep the prefix the same across queries.
+pub struct Bitflip<F>
+where
+    F: Fn(&[u8]) -> Vec<u8>,
+{
+    oracle: F,
+    mode: FlipMode,
+}
+
+impl<F> Bitflip<F>
+where
+    F: Fn(&[u8]) -> Vec<u8>,
+{
+    pub fn new(oracle: F, mode: FlipMode) -> Self {
+        Bitflip { oracle, mode }
+    }
+
+    /// Index of the first unit of ciphertext affected by `fill_len` filler bytes
+    /// followed by a single byte
+    fn first_changed_unit(&self, fill_len: usize) -> Result<usize, BitflipError> {
+        let unit = self.mode.unit();
+        let mut input = vec![b'A'; fill_len + 1];
+        let a = (self.oracle)(&input);
+        input[fill_len] = b'B';
+        let b = (self.oracle)(&input);
+        a.chunks(unit)
+            .zip(b.chunks(unit))
+            .position(|(a, b)| a != b)
+            .ok_or(BitflipError::PrefixNotFound)
+    }
+
+    /// Find the length of the prefix
+    pub fn prefix_len(&self) -> Result<usize, BitflipError> {
+        let unit = self.mode.unit();
+        if unit == 0 {
+            return Err(BitflipError::InvalidBlockSize);
+        }
+        let first = self.first_changed_unit(0)?;
+        // The changed unit moves on once the filler completes the unit holding the prefix end
+        for fill_len in 1..=unit {
+            if self.first_changed_unit(fill_len)? > first {
+                return Ok((first + 1) * unit - fill_len);
+            }
+        }
+        Err(BitflipError::PrefixNotFound)
+    }
+
+    /// Forge a ciphertext that decrypts to contain the payload, even though the
+    /// oracle would quote it
+    ///
+    /// In CBC mode every block of the payload is preceded by a sacrificial block
+    /// absorbing its flips, so longer payloads decrypt to the payload blocks
+    /// interleaved with garbage blocks.
+    pub fn inject<T>(&self, payload: T) -> Result<Vec<u8>, BitflipError>
+    where
+        T: AsRef<[u8]>,
+    {
+        let payload = payload.as_ref();
+        let prefix_len = self.prefix_len()?;
+        let known = vec![b'A'; payload.len()];
+        match self.mode {
+            FlipMode::Cbc { block_size } => {
+                // Align to a block boundary, then sacrifice one block to the
+                // flips of every payload block
+                let align = (block_size - prefix_len % block_size) % block_size;
+                let mut input = vec![b'A'; align];
+                for chunk in known.chunks(block_size) {
+                    input.extend(vec![b'A'; block_size]);
+                    input.extend(chunk);
+                }
+                let mut ciphertext = (self.oracle)(&input);
+                let start = prefix_len + align;
+                for (i, (known, desired)) in known
+                    .chunks(block_size)
+                    .zip(payload.chunks(block_size))
+                    .enumerate()
+                {
+                    let offset = start + (2 * i + 1) * block_size;
+                    flip_cbc(&mut ciphertext, block_size, offset, known, desired)?;
+                }
+                Ok(ciphertext)
+            }
+            FlipMode::Ctr => {
+                let mut ciphertext = (self.oracle)(&known);
+                flip_ctr(&mut ciphertext, prefix_len, &known, payload)?;
+                Ok(ciphertext)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_bitflip {
+    use super::{flip_cbc, flip_ctr, xor_delta, Bitflip, BitflipError, FlipMode};
+    use crate::block::{cbc::Cbc, ctr::CounterLayout, ctr::Ctr, BlockMode};
+
+    static KEY: &[u8] = b"YELLOW SUBMARINE";
+
+    fn wrap(prefix: &[u8], input: &[u8]) -> Vec<u8> {
+        let quoted = input
+            .iter()
+            .flat_map(|&b| match b {
+                b';' | b'=' => vec![b'?'],
+                b => vec![b],
+            })
+            .collect::<Vec<_>>();
+        [prefix, &quoted[..], b";suffix"].concat()
+    }
+
+    #[test]
+    fn test_xor_delta() {
+        assert_eq!(vec![0, 1, 0x20], xor_delta("abc", "acC").unwrap());
+        assert_eq!(
+            Err(BitflipError::LengthMismatch {
+                known: 2,
+                desired: 3
+            }),
+            xor_delta("ab", "abc")
+        );
+    }
+
+    #[test]
+    fn test_flip_ctr() {
+        let mut ctr = Ctr::new(KEY, &[0; 8], CounterLayout::LittleEndian64).unwrap();
+        let mut ciphertext = ctr.process(b"role=user;");
+        flip_ctr(&mut ciphertext, 5, "user", "root").unwrap();
+        let mut ctr = Ctr::new(KEY, &[0; 8], CounterLayout::LittleEndian64).unwrap();
+        assert_eq!(b"role=root;", &ctr.process(&ciphertext)[..]);
+        assert_eq!(
+            Err(BitflipError::OutOfRange { offset: 7, len: 10 }),
+            flip_ctr(&mut ciphertext, 7, "user", "root")
+        );
+        assert_eq!(
+            Err(BitflipError::OutOfRange {
+                offset: usize::MAX,
+                len: 10
+            }),
+            flip_ctr(&mut ciphertext, usize::MAX, "u", "r")
+        );
+    }
+
+    #[test]
+    fn test_flip_cbc() {
+        let cbc = Cbc::new(KEY, &[0; 16]).unwrap();
+        let plaintext = b"0123456789abcdefrole=user;......";
+        let mut ciphertext = cbc.encrypt(plaintext);
+        flip_cbc(&mut ciphertext, 16, 21, "user", "root").unwrap();
+        let actual = cbc.decrypt(&ciphertext).unwrap();
+        assert_eq!(b"role=root;......", &actual[16..32]);
+        assert_ne!(&plaintext[..16], &actual[..16]);
+        assert_eq!(
+            Err(BitflipError::NoPrecedingBlock { offset: 5 }),
+            flip_cbc(&mut ciphertext, 16, 5, "a", "b")
+        );
+        assert_eq!(
+            Err(BitflipError::SpansBlocks { offset: 30, len: 4 }),
+            flip_cbc(&mut ciphertext, 16, 30, "abcd", "efgh")
+        );
+        assert_eq!(
+            Err(BitflipError::OutOfRange {
+                offset: 46,
+                len: 48
+            }),
+            flip_cbc(&mut ciphertext, 16, 46, "abc", "def")
+        );
+        assert_eq!(
+            Err(BitflipError::OutOfRange {
+                offset: usize::MAX,
+                len: 48
+            }),
+            flip_cbc(&mut ciphertext, 16, usize::MAX, "a", "b")
+        );
+    }
+
+    #[test]
+    fn test_inject_cbc() {
+        let cbc = Cbc::new(KEY, &[7; 16]).unwrap();
+        for prefix_len in [0, 1, 15, 16, 17, 32] {
+            let prefix = vec![b'p'; prefix_len];
+            let attack = Bitflip::new(
+                |input: &[u8]| cbc.encrypt(&wrap(&prefix, input)),
+                FlipMode::Cbc { block_size: 16 },
+            );
+            assert_eq!(prefix_len, attack.prefix_len().unwrap());
+            let plaintext = cbc
+                .decrypt(&attack.inject(";admin=true;").unwrap())
+                .unwrap();
+            assert!(
+                plaintext.windows(12).any(|w| w == b";admin=true;"),
+                "prefix {}",
+                prefix_len
+            );
+        }
+    }
+
+    #[test]
+    fn test_inject_cbc_long_payload() {
+        let cbc = Cbc::new(KEY, &[7; 16]).unwrap();
+        let attack = Bitflip::new(
+            |input: &[u8]| cbc.encrypt(&wrap(b"prefix", input)),
+            FlipMode::Cbc { block_size: 16 },
+        );
+        let payload = b";admin=true;role=root;uid=0;";
+        let plaintext = cbc.decrypt(&attack.inject(payload).unwrap()).unwrap();
+        // Payload blocks are interleaved with garbled sacrificial blocks
+        assert_eq!(&payload[..16], &plaintext[32..48]);
+        assert_eq!(&payload[16..], &plaintext[64..76]);
+    }
+
+    #[test]
+    fn test_inject_ctr() {
+        let ctr = |data: &[u8]| {
+            Ctr::new(KEY, &[3; 8], CounterLayout::LittleEndian64)
+                .unwrap()
+                .process(data)
+        };
+        for prefix_len in [0, 1, 16, 33] {
+            let prefix = vec![b'p'; prefix_len];
+            let attack = Bitflip::new(|input: &[u8]| ctr(&wrap(&prefix, input)), FlipMode::Ctr);
+            assert_eq!(prefix_len, attack.prefix_len().unwrap());
+            let plaintext = ctr(&attack.inject(";admin=true;").unwrap());
+            assert_eq!(wrap(&prefix, b"").len() + 12, plaintext.len());
+            assert_eq!(b";admin=true;", &plaintext[prefix_len..prefix_len + 12]);
+        }
+    }
+
+    #[test]
+    fn test_prefix_not_found() {
+        let attack = Bitflip::new(|_: &[u8]| vec![0; 16], FlipMode::Ctr);
+        assert_eq!(Err(BitflipError::PrefixNotFound), attack.inject("x"));
+    }
+
+    #[test]
+    fn test_invalid_block_size() {
+        assert_eq!(
+            Err(BitflipError::InvalidBlockSize),
+            flip_cbc(&mut [0; 32], 0, 16, "a", "b")
+        );
+        let attack = Bitflip::new(
+            |input: &[u8]| input.to_vec(),
+            FlipMode::Cbc { block_size: 0 },
+        );
+        assert_eq!(Err(BitflipError::InvalidBlockSize), attack.inject("x"));
+    }
+}
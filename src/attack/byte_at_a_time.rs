@@ -0,0 +1,259 @@
+use std::cell::Cell;
+use std::{error::Error, fmt};
+
+use crate::block::detect::{detect_oracle_mode, CipherMode};
+
+/// Largest block size probed for, in bytes
+const MAX_BLOCK_SIZE: usize = 256;
+
+/// Error produced when attacking an encryption oracle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttackError {
+    /// The ciphertext length never grew by a whole block
+    BlockSizeNotFound,
+    /// The oracle doesn't encrypt using ECB
+    NotEcb,
+    /// Our input never lined up into two identical ciphertext blocks
+    PrefixNotFound,
+    /// No candidate byte matched the secret byte at the given offset
+    ByteNotFound { offset: usize },
+}
+
+impl fmt::Display for AttackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttackError::BlockSizeNotFound => write!(f, "block size not found"),
+            AttackError::NotEcb => write!(f, "oracle doesn't use ECB"),
+            AttackError::PrefixNotFound => write!(f, "prefix length not found"),
+            AttackError::ByteNotFound { offset } => {
+                write!(
+                    f,
+                    "no candidate matches the secret byte at offset {}",
+                    offset
+                )
+            }
+        }
+    }
+}
+
+impl Error for AttackError {}
+
+/// Result of a byte-at-a-time attack
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recovery {
+    /// The secret appended to our input by the oracle
+    pub secret: Vec<u8>,
+    pub block_size: usize,
+    /// Length of the fixed prefix prepended to our input by the oracle
+    pub prefix_len: usize,
+    /// Number of oracle queries used
+    pub queries: usize,
+}
+
+/// Byte-at-a-time attack on an oracle encrypting `prefix || input || secret`
+/// using ECB under a fixed key
+///
+/// The prefix may have any length, but must stay the same across queries.
+pub struct ByteAtATime<F>
+where
+    F: Fn(&[u8]) -> Vec<u8>,
+{
+    oracle: F,
+    queries: Cell<usize>,
+}
+
+impl<F> ByteAtATime<F>
+where
+    F: Fn(&[u8]) -> Vec<u8>,
+{
+    pub fn new(oracle: F) -> Self {
+        ByteAtATime {
+            oracle,
+            queries: Cell::new(0),
+        }
+    }
+
+    fn query(&self, input: &[u8]) -> Vec<u8> {
+        self.queries.set(self.queries.get() + 1);
+        (self.oracle)(input)
+    }
+
+    /// Find the block size by growing the input until the ciphertext grows,
+    /// returning it with the combined length of the prefix and secret
+    fn block_size(&self) -> Result<(usize, usize), AttackError> {
+        let base_len = self.query(b"").len();
+        for len in 1..=MAX_BLOCK_SIZE {
+            let grown_len = self.query(&vec![b'A'; len]).len();
+            if grown_len > base_len {
+                // The input just filled the last block, pushing a full block of padding into a new one
+                let block_size = grown_len - base_len;
+                return Ok((block_size, base_len - len));
+            }
+        }
+        Err(AttackError::BlockSizeNotFound)
+    }
+
+    /// Find the index of the first ciphertext block affected by our input
+    fn first_input_block(&self, block_size: usize) -> Result<usize, AttackError> {
+        let a = self.query(&[0x00]);
+        let b = self.query(&[0x01]);
+        a.chunks(block_size)
+            .zip(b.chunks(block_size))
+            .position(|(a, b)| a != b)
+            .ok_or(AttackError::PrefixNotFound)
+    }
+
+    /// Find the offset and ciphertext of the first pair of identical blocks
+    /// produced by `align` bytes followed by two blocks of the fill byte
+    fn duplicate_pair(
+        &self,
+        block_size: usize,
+        first: usize,
+        align: usize,
+        fill: u8,
+    ) -> Option<(usize, Vec<u8>)> {
+        let mut input = vec![b'A'; align];
+        input.extend(vec![fill; 2 * block_size]);
+        let ciphertext = self.query(&input);
+        let blocks = ciphertext.chunks_exact(block_size).collect::<Vec<_>>();
+        let i = blocks.get(first..)?.windows(2).position(|w| w[0] == w[1])?;
+        Some(((first + i) * block_size, blocks[first + i].to_vec()))
+    }
+
+    /// Find the number of fill bytes completing the last prefix block, and
+    /// the offset of the first block fully under our control
+    fn alignment(&self, block_size: usize) -> Result<(usize, usize), AttackError> {
+        let first = self.first_input_block(block_size)?;
+        // Prefix, secret or padding bytes may coincide with a single fill byte,
+        // but not with two different ones. Repeated blocks of the secret stay
+        // the same for both fill bytes, while the blocks of our fill don't.
+        (0..block_size)
+            .find_map(|align| {
+                let (a, block_a) = self.duplicate_pair(block_size, first, align, 0x00)?;
+                let (b, block_b) = self.duplicate_pair(block_size, first, align, 0x01)?;
+                (a == b && block_a != block_b).then_some((align, a))
+            })
+            .ok_or(AttackError::PrefixNotFound)
+    }
+
+    /// Recover the secret appended by the oracle
+    pub fn recover(&self) -> Result<Recovery, AttackError> {
+        self.queries.set(0);
+        let (block_size, affix_len) = self.block_size()?;
+        if detect_oracle_mode(|input| self.query(input), block_size) != CipherMode::Ecb {
+            return Err(AttackError::NotEcb);
+        }
+        let (align, start) = self.alignment(block_size)?;
+        let prefix_len = start - align;
+        let secret_len = affix_len
+            .checked_sub(prefix_len)
+            .ok_or(AttackError::PrefixNotFound)?;
+        let mut secret = Vec::with_capacity(secret_len);
+        for offset in 0..secret_len {
+            // Shift the unknown byte to the end of a block
+            let fill_len = block_size - 1 - offset % block_size;
+            let mut input = vec![b'A'; align + fill_len];
+            let ciphertext = self.query(&input);
+            let target = start + (offset / block_size) * block_size;
+            let target = &ciphertext[target..target + block_size];
+            // Encrypt all 256 candidate blocks in a single query
+            let mut known = vec![b'A'; block_size - 1];
+            known.extend(&secret);
+            let window = &known[known.len() - (block_size - 1)..];
+            input.truncate(align);
+            for b in 0..=u8::MAX {
+                input.extend(window);
+                input.push(b);
+            }
+            let dictionary = self.query(&input);
+            let b = dictionary[start..]
+                .chunks_exact(block_size)
+                .take(256)
+                .position(|block| block == target)
+                .ok_or(AttackError::ByteNotFound { offset })?;
+            secret.push(b as u8);
+        }
+        Ok(Recovery {
+            secret,
+            block_size,
+            prefix_len,
+            queries: self.queries.get(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_byte_at_a_time {
+    use super::{AttackError, ByteAtATime};
+    use crate::block::{cbc::Cbc, ecb::Ecb, BlockMode};
+
+    static SECRET: &[u8] = b"Attack at dawn, bring the maps!\n";
+
+    fn ecb_oracle(prefix: &[u8], secret: &[u8]) -> impl Fn(&[u8]) -> Vec<u8> {
+        let ecb = Ecb::new(b"YELLOW SUBMARINE").unwrap();
+        let (prefix, secret) = (prefix.to_vec(), secret.to_vec());
+        move |input| ecb.encrypt(&[&prefix[..], input, &secret[..]].concat())
+    }
+
+    #[test]
+    fn test_recover_prefix_lengths() {
+        for prefix_len in [0, 1, 5, 15, 16, 17, 40] {
+            let prefix = vec![0x01; prefix_len];
+            let actual = ByteAtATime::new(ecb_oracle(&prefix, SECRET))
+                .recover()
+                .unwrap();
+            assert_eq!(SECRET, actual.secret, "prefix {}", prefix_len);
+            assert_eq!(16, actual.block_size);
+            assert_eq!(prefix_len, actual.prefix_len);
+        }
+    }
+
+    #[test]
+    fn test_recover_secret_lengths() {
+        for secret_len in [0, 1, 15, 16, 17] {
+            let actual = ByteAtATime::new(ecb_oracle(b"xyz", &SECRET[..secret_len]))
+                .recover()
+                .unwrap();
+            assert_eq!(&SECRET[..secret_len], actual.secret);
+        }
+    }
+
+    #[test]
+    fn test_recover_repeated_block_secret() {
+        // Repeated blocks of the secret must not be mistaken for our own fill
+        let secret = [&[b'Z'; 64][..], b"tail text here"].concat();
+        for prefix_len in 0..=17 {
+            let prefix = vec![0x01; prefix_len];
+            let actual = ByteAtATime::new(ecb_oracle(&prefix, &secret))
+                .recover()
+                .unwrap();
+            assert_eq!(secret, actual.secret, "prefix {}", prefix_len);
+            assert_eq!(prefix_len, actual.prefix_len);
+        }
+    }
+
+    #[test]
+    fn test_query_count() {
+        let actual = ByteAtATime::new(ecb_oracle(b"", SECRET)).recover().unwrap();
+        // Two queries per secret byte, on top of the block size, mode and alignment probes
+        assert!(actual.queries > 2 * SECRET.len());
+        assert!(
+            actual.queries <= 2 * SECRET.len() + 64,
+            "{}",
+            actual.queries
+        );
+    }
+
+    #[test]
+    fn test_not_ecb() {
+        let cbc = Cbc::new(b"YELLOW SUBMARINE", &[0; 16]).unwrap();
+        let attack = ByteAtATime::new(|input: &[u8]| cbc.encrypt(input));
+        assert_eq!(Err(AttackError::NotEcb), attack.recover());
+    }
+
+    #[test]
+    fn test_block_size_not_found() {
+        let attack = ByteAtATime::new(|_: &[u8]| vec![0; 16]);
+        assert_eq!(Err(AttackError::BlockSizeNotFound), attack.recover());
+    }
+}
@@ -0,0 +1,194 @@
+use std::{error::Error, fmt};
+
+use aes::{
+    cipher::{generic_array::GenericArray, BlockDecrypt, BlockEncrypt, KeyInit},
+    Aes128, Aes192, Aes256,
+};
+
+pub mod cbc;
+pub mod ctr;
+pub mod detect;
+pub mod ecb;
+pub mod padding;
+
+use padding::PaddingError;
+
+/// Block size of AES, in bytes
+pub const BLOCK_SIZE: usize = 16;
+
+/// Error produced by block cipher operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockError {
+    /// The key isn't 16, 24 or 32 bytes long
+    InvalidKeyLength { len: usize },
+    /// The IV isn't one block long
+    InvalidIvLength { len: usize },
+    /// The nonce doesn't match the length required by the counter layout
+    InvalidNonceLength { len: usize },
+    /// The input isn't a whole number of blocks
+    InvalidLength { len: usize, block_len: usize },
+    /// The decrypted plaintext doesn't end with valid PKCS#7 padding
+    Padding(PaddingError),
+}
+
+impl fmt::Display for BlockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockError::InvalidKeyLength { len } => write!(f, "invalid key length {}", len),
+            BlockError::InvalidIvLength { len } => write!(f, "invalid IV length {}", len),
+            BlockError::InvalidNonceLength { len } => write!(f, "invalid nonce length {}", len),
+            BlockError::InvalidLength { len, block_len } => {
+                write!(f, "input length {} is not a multiple of {}", len, block_len)
+            }
+            BlockError::Padding(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for BlockError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BlockError::Padding(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<PaddingError> for BlockError {
+    fn from(e: PaddingError) -> Self {
+        BlockError::Padding(e)
+    }
+}
+
+/// AES block cipher, with the variant selected by the key length
+#[derive(Clone)]
+pub enum Aes {
+    Aes128(Aes128),
+    Aes192(Aes192),
+    Aes256(Aes256),
+}
+
+impl Aes {
+    /// Create a cipher from a 16, 24 or 32-byte key
+    pub fn new(key: &[u8]) -> Result<Self, BlockError> {
+        let len = key.len();
+        let invalid = |_| BlockError::InvalidKeyLength { len };
+        match len {
+            16 => Aes128::new_from_slice(key)
+                .map(Aes::Aes128)
+                .map_err(invalid),
+            24 => Aes192::new_from_slice(key)
+                .map(Aes::Aes192)
+                .map_err(invalid),
+            32 => Aes256::new_from_slice(key)
+                .map(Aes::Aes256)
+                .map_err(invalid),
+            _ => Err(BlockError::InvalidKeyLength { len }),
+        }
+    }
+
+    /// Encrypt a single block in place
+    pub fn encrypt_block(&self, block: &mut [u8; BLOCK_SIZE]) {
+        let block = GenericArray::from_mut_slice(block);
+        match self {
+            Aes::Aes128(cipher) => cipher.encrypt_block(block),
+            Aes::Aes192(cipher) => cipher.encrypt_block(block),
+            Aes::Aes256(cipher) => cipher.encrypt_block(block),
+        }
+    }
+
+    /// Decrypt a single block in place
+    pub fn decrypt_block(&self, block: &mut [u8; BLOCK_SIZE]) {
+        let block = GenericArray::from_mut_slice(block);
+        match self {
+            Aes::Aes128(cipher) => cipher.decrypt_block(block),
+            Aes::Aes192(cipher) => cipher.decrypt_block(block),
+            Aes::Aes256(cipher) => cipher.decrypt_block(block),
+        }
+    }
+}
+
+impl fmt::Debug for Aes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Don't leak the expanded key
+        let name = match self {
+            Aes::Aes128(_) => "Aes128",
+            Aes::Aes192(_) => "Aes192",
+            Aes::Aes256(_) => "Aes256",
+        };
+        f.debug_struct(name).finish_non_exhaustive()
+    }
+}
+
+/// Check that the input is a whole number of blocks
+fn check_length(s: &[u8]) -> Result<(), BlockError> {
+    if s.len().is_multiple_of(BLOCK_SIZE) {
+        Ok(())
+    } else {
+        Err(BlockError::InvalidLength {
+            len: s.len(),
+            block_len: BLOCK_SIZE,
+        })
+    }
+}
+
+/// Block cipher mode of operation
+pub trait BlockMode {
+    /// Encrypt a plaintext consisting of whole blocks, without padding
+    fn encrypt_blocks(&self, plaintext: &[u8]) -> Result<Vec<u8>, BlockError>;
+
+    /// Decrypt a ciphertext consisting of whole blocks, without removing padding
+    fn decrypt_blocks(&self, ciphertext: &[u8]) -> Result<Vec<u8>, BlockError>;
+
+    /// Encrypt a plaintext of any length, padding it using PKCS#7
+    fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        // The block size is valid, and padded plaintexts are always a whole number of blocks
+        self.encrypt_blocks(&padding::pad(plaintext, BLOCK_SIZE).unwrap())
+            .unwrap()
+    }
+
+    /// Decrypt a ciphertext and strip its PKCS#7 padding
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, BlockError> {
+        let mut plaintext = self.decrypt_blocks(ciphertext)?;
+        plaintext.truncate(padding::unpadded_len(&plaintext, BLOCK_SIZE)?);
+        Ok(plaintext)
+    }
+}
+
+#[cfg(test)]
+mod test_block {
+    use super::{Aes, BlockError};
+    use crate::codec::hex;
+
+    /// Example vectors from FIPS-197 appendix C
+    #[test]
+    fn test_aes_vectors() {
+        let plaintext = hex::decode("00112233445566778899aabbccddeeff").unwrap();
+        let vectors = [
+            (16, "69c4e0d86a7b0430d8cdb78070b4c55a"),
+            (24, "dda97ca4864cdfe06eaf70a0ec0d7191"),
+            (32, "8ea2b7ca516745bfeafc49904b496089"),
+        ];
+        for (key_len, expected) in vectors {
+            let key = (0..key_len as u8).collect::<Vec<_>>();
+            let cipher = Aes::new(&key).unwrap();
+            let mut block = plaintext.clone().try_into().unwrap();
+            cipher.encrypt_block(&mut block);
+            assert_eq!(expected, hex::encode(block));
+            cipher.decrypt_block(&mut block);
+            assert_eq!(&plaintext[..], block);
+        }
+    }
+
+    #[test]
+    fn test_aes_invalid_key_length() {
+        for len in [0, 15, 17, 31, 33] {
+            let expected = BlockError::InvalidKeyLength { len };
+            assert_eq!(expected, Aes::new(&vec![0; len]).unwrap_err());
+        }
+        assert_eq!(
+            "Aes192 { .. }",
+            format!("{:?}", Aes::new(&[0; 24]).unwrap())
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use super::{check_length, Aes, BlockError, BlockMode, BLOCK_SIZE};
+use crate::xor::xor_into;
+
+/// Cipher block chaining mode, xoring every plaintext block with the previous ciphertext block
+#[derive(Debug, Clone)]
+pub struct Cbc {
+    cipher: Aes,
+    iv: [u8; BLOCK_SIZE],
+}
+
+impl Cbc {
+    /// Create a CBC mode cipher from a 16, 24 or 32-byte AES key and a 16-byte IV
+    pub fn new(key: &[u8], iv: &[u8]) -> Result<Self, BlockError> {
+        let iv = iv
+            .try_into()
+            .map_err(|_| BlockError::InvalidIvLength { len: iv.len() })?;
+        Ok(Cbc {
+            cipher: Aes::new(key)?,
+            iv,
+        })
+    }
+
+    pub fn iv(&self) -> &[u8; BLOCK_SIZE] {
+        &self.iv
+    }
+}
+
+impl BlockMode for Cbc {
+    fn encrypt_blocks(&self, plaintext: &[u8]) -> Result<Vec<u8>, BlockError> {
+        check_length(plaintext)?;
+        let mut out = plaintext.to_vec();
+        let mut previous = self.iv;
+        for block in out.chunks_exact_mut(BLOCK_SIZE) {
+            // Both blocks are always BLOCK_SIZE bytes long
+            xor_into(block, previous).unwrap();
+            let block: &mut [u8; BLOCK_SIZE] = block.try_into().unwrap();
+            self.cipher.encrypt_block(block);
+            previous = *block;
+        }
+        Ok(out)
+    }
+
+    fn decrypt_blocks(&self, ciphertext: &[u8]) -> Result<Vec<u8>, BlockError> {
+        check_length(ciphertext)?;
+        let mut out = ciphertext.to_vec();
+        let mut previous = self.iv;
+        for block in out.chunks_exact_mut(BLOCK_SIZE) {
+            let block: &mut [u8; BLOCK_SIZE] = block.try_into().unwrap();
+            let current = *block;
+            self.cipher.decrypt_block(block);
+            xor_into(block, previous).unwrap();
+            previous = current;
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod test_cbc {
+    use super::Cbc;
+    use crate::block::{ecb::Ecb, BlockError, BlockMode};
+    use crate::codec::hex;
+
+    /// Example vector from NIST SP 800-38A F.2.1
+    #[test]
+    fn test_nist_vector() {
+        let key = hex::decode("2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let iv = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let plaintext =
+            hex::decode("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51")
+                .unwrap();
+        let expected = "7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b2";
+        let cbc = Cbc::new(&key, &iv).unwrap();
+        let actual = cbc.encrypt_blocks(&plaintext).unwrap();
+        assert_eq!(expected, hex::encode(&actual));
+        assert_eq!(Ok(plaintext), cbc.decrypt_blocks(&actual));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for key_len in [16, 24, 32] {
+            let cbc = Cbc::new(&vec![0x42; key_len], &[7; 16]).unwrap();
+            for len in 0..50 {
+                let plaintext = vec![b'x'; len];
+                let ciphertext = cbc.encrypt(&plaintext);
+                assert_eq!((len / 16 + 1) * 16, ciphertext.len());
+                assert_eq!(Ok(plaintext), cbc.decrypt(&ciphertext));
+            }
+        }
+    }
+
+    #[test]
+    fn test_identical_blocks_differ() {
+        let key = b"YELLOW SUBMARINE";
+        let cbc = Cbc::new(key, &[0; 16]).unwrap();
+        let actual = cbc.encrypt_blocks(&[b'A'; 32]).unwrap();
+        assert_ne!(actual[..16], actual[16..]);
+        // With a zero IV, the first block is plain ECB
+        let ecb = Ecb::new(key).unwrap();
+        assert_eq!(ecb.encrypt_blocks(&[b'A'; 16]).unwrap(), actual[..16]);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Err(BlockError::InvalidIvLength { len: 8 }),
+            Cbc::new(b"YELLOW SUBMARINE", &[0; 8]).map(|_| ())
+        );
+        let cbc = Cbc::new(b"YELLOW SUBMARINE", &[0; 16]).unwrap();
+        let expected = BlockError::InvalidLength {
+            len: 20,
+            block_len: 16,
+        };
+        assert_eq!(Err(expected), cbc.decrypt(&[0; 20]));
+    }
+}
@@ -0,0 +1,289 @@
+use std::io::{self, Write};
+
+use super::{Aes, BlockError, BLOCK_SIZE};
+use crate::xor::xor_into;
+
+/// Arrangement of the nonce and block counter within a counter block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CounterLayout {
+    /// 8-byte nonce followed by a little-endian 64-bit counter, as used by cryptopals
+    LittleEndian64,
+    /// 8-byte nonce followed by a big-endian 64-bit counter, as in NIST SP 800-38A
+    BigEndian64,
+    /// 12-byte nonce followed by a big-endian 32-bit counter, as used by GCM
+    BigEndian32,
+}
+
+impl CounterLayout {
+    /// Length of the nonce preceding the counter, in bytes
+    pub fn nonce_len(&self) -> usize {
+        match self {
+            CounterLayout::LittleEndian64 | CounterLayout::BigEndian64 => 8,
+            CounterLayout::BigEndian32 => 12,
+        }
+    }
+}
+
+/// Counter mode, turning AES into a seekable stream cipher
+///
+/// Encryption and decryption are the same operation. Counters wrap around
+/// when they overflow the counter field of the layout, and the position wraps
+/// around to the start of the keystream past `u64::MAX`.
+#[derive(Debug, Clone)]
+pub struct Ctr {
+    cipher: Aes,
+    nonce: Vec<u8>,
+    layout: CounterLayout,
+    initial_counter: u64,
+    /// Offset of the next keystream byte
+    position: u64,
+}
+
+impl Ctr {
+    /// Create a CTR mode cipher from a 16, 24 or 32-byte AES key and a nonce
+    /// whose length matches the counter layout
+    pub fn new(key: &[u8], nonce: &[u8], layout: CounterLayout) -> Result<Self, BlockError> {
+        if nonce.len() != layout.nonce_len() {
+            return Err(BlockError::InvalidNonceLength { len: nonce.len() });
+        }
+        Ok(Ctr {
+            cipher: Aes::new(key)?,
+            nonce: nonce.to_vec(),
+            layout,
+            initial_counter: 0,
+            position: 0,
+        })
+    }
+
+    /// Set the value of the counter of the first keystream block
+    pub fn with_initial_counter(self, initial_counter: u64) -> Self {
+        Ctr {
+            initial_counter,
+            ..self
+        }
+    }
+
+    /// Offset of the next keystream byte
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Move to an arbitrary offset in the keystream
+    pub fn seek(&mut self, position: u64) {
+        self.position = position;
+    }
+
+    /// Generate the keystream block with the given index
+    fn keystream_block(&self, index: u64) -> [u8; BLOCK_SIZE] {
+        let counter = self.initial_counter.wrapping_add(index);
+        let mut block = [0; BLOCK_SIZE];
+        block[..self.nonce.len()].copy_from_slice(&self.nonce);
+        match self.layout {
+            CounterLayout::LittleEndian64 => block[8..].copy_from_slice(&counter.to_le_bytes()),
+            CounterLayout::BigEndian64 => block[8..].copy_from_slice(&counter.to_be_bytes()),
+            CounterLayout::BigEndian32 => {
+                block[12..].copy_from_slice(&(counter as u32).to_be_bytes())
+            }
+        }
+        self.cipher.encrypt_block(&mut block);
+        block
+    }
+
+    /// Xor the keystream into a buffer in place, advancing the position
+    pub fn apply_keystream(&mut self, data: &mut [u8]) {
+        let mut data = data;
+        while !data.is_empty() {
+            let offset = (self.position % BLOCK_SIZE as u64) as usize;
+            let keystream = self.keystream_block(self.position / BLOCK_SIZE as u64);
+            let len = data.len().min(BLOCK_SIZE - offset);
+            let (chunk, rest) = data.split_at_mut(len);
+            // Both slices are always `len` bytes long
+            xor_into(chunk, &keystream[offset..offset + len]).unwrap();
+            self.position = self.position.wrapping_add(len as u64);
+            data = rest;
+        }
+    }
+
+    /// Encrypt or decrypt a byte slice, advancing the position
+    pub fn process(&mut self, data: &[u8]) -> Vec<u8> {
+        let mut out = data.to_vec();
+        self.apply_keystream(&mut out);
+        out
+    }
+}
+
+/// Adapter that encrypts or decrypts everything written to it using CTR mode
+pub struct CtrWriter<W: Write> {
+    inner: W,
+    ctr: Ctr,
+}
+
+impl<W: Write> CtrWriter<W> {
+    pub fn new(inner: W, ctr: Ctr) -> Self {
+        CtrWriter { inner, ctr }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for CtrWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Writing everything keeps the keystream position in sync with the output,
+        // and on failure none of the input counts as written
+        let position = self.ctr.position();
+        let out = self.ctr.process(buf);
+        if let Err(e) = self.inner.write_all(&out) {
+            self.ctr.seek(position);
+            return Err(e);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod test_ctr {
+    use std::io::Write;
+
+    use super::{CounterLayout, Ctr, CtrWriter};
+    use crate::block::BlockError;
+    use crate::codec::hex;
+
+    fn ctr(layout: CounterLayout) -> Ctr {
+        let nonce = vec![0x24; layout.nonce_len()];
+        Ctr::new(b"YELLOW SUBMARINE", &nonce, layout).unwrap()
+    }
+
+    /// Example vector from NIST SP 800-38A F.5.1
+    #[test]
+    fn test_nist_vector() {
+        let key = hex::decode("2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let nonce = hex::decode("f0f1f2f3f4f5f6f7").unwrap();
+        let plaintext = hex::decode("6bc1bee22e409f96e93d7e117393172a").unwrap();
+        let mut ctr = Ctr::new(&key, &nonce, CounterLayout::BigEndian64)
+            .unwrap()
+            .with_initial_counter(0xf8f9fafbfcfdfeff);
+        let actual = ctr.process(&plaintext);
+        assert_eq!("874d6191b620e3261bef6864990db6ce", hex::encode(actual));
+    }
+
+    #[test]
+    fn test_layouts() {
+        let input = [0u8; 48];
+        let keystreams = [
+            CounterLayout::LittleEndian64,
+            CounterLayout::BigEndian64,
+            CounterLayout::BigEndian32,
+        ]
+        .map(|layout| ctr(layout).process(&input));
+        // The first counter block is all zeroes in every layout but the nonce
+        assert_eq!(keystreams[0][..16], keystreams[1][..16]);
+        assert_ne!(keystreams[0][16..], keystreams[1][16..]);
+        assert_ne!(keystreams[1][..16], keystreams[2][..16]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let plaintext = b"Counter mode needs no padding at all";
+        let ciphertext = ctr(CounterLayout::LittleEndian64).process(plaintext);
+        assert_eq!(plaintext.len(), ciphertext.len());
+        let actual = ctr(CounterLayout::LittleEndian64).process(&ciphertext);
+        assert_eq!(&plaintext[..], actual);
+    }
+
+    #[test]
+    fn test_seek() {
+        let input = (0..100).collect::<Vec<u8>>();
+        let expected = ctr(CounterLayout::BigEndian32).process(&input);
+        for offset in [0, 1, 15, 16, 17, 63, 99, 100] {
+            let mut ctr = ctr(CounterLayout::BigEndian32);
+            ctr.seek(offset as u64);
+            assert_eq!(expected[offset..], ctr.process(&input[offset..])[..]);
+            assert_eq!(100, ctr.position());
+        }
+    }
+
+    #[test]
+    fn test_counter_wraps() {
+        let input = [0u8; 32];
+        let mut ctr = ctr(CounterLayout::BigEndian32).with_initial_counter(u32::MAX as u64);
+        let actual = ctr.process(&input);
+        let expected = self::ctr(CounterLayout::BigEndian32).process(&input);
+        assert_eq!(expected[..16], actual[16..]);
+    }
+
+    #[test]
+    fn test_position_wraps() {
+        let input = [0u8; 8];
+        let mut ctr = ctr(CounterLayout::LittleEndian64);
+        ctr.seek(u64::MAX - 3);
+        let actual = ctr.process(&input);
+        assert_eq!(4, ctr.position());
+        let mut expected = self::ctr(CounterLayout::LittleEndian64);
+        expected.seek(u64::MAX - 3);
+        assert_eq!(expected.process(&input[..4])[..], actual[..4]);
+        expected.seek(0);
+        assert_eq!(expected.process(&input[4..])[..], actual[4..]);
+    }
+
+    #[test]
+    fn test_writer() {
+        let input = (0..=255).collect::<Vec<u8>>();
+        let expected = ctr(CounterLayout::LittleEndian64).process(&input);
+        let mut writer = CtrWriter::new(Vec::new(), ctr(CounterLayout::LittleEndian64));
+        for chunk in input.chunks(7) {
+            writer.write_all(chunk).unwrap();
+        }
+        writer.flush().unwrap();
+        assert_eq!(expected, writer.into_inner());
+    }
+
+    /// Writer failing its first write
+    struct Flaky {
+        failed: bool,
+        out: Vec<u8>,
+    }
+
+    impl Write for Flaky {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if !self.failed {
+                self.failed = true;
+                return Err(std::io::Error::other("flaky"));
+            }
+            self.out.extend(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_writer_retry_after_error() {
+        let input = b"Counter mode needs no padding at all";
+        let expected = ctr(CounterLayout::LittleEndian64).process(input);
+        let inner = Flaky {
+            failed: false,
+            out: Vec::new(),
+        };
+        let mut writer = CtrWriter::new(inner, ctr(CounterLayout::LittleEndian64));
+        writer.write_all(input).unwrap_err();
+        writer.write_all(input).unwrap();
+        assert_eq!(expected, writer.into_inner().out);
+    }
+
+    #[test]
+    fn test_invalid_nonce_length() {
+        let actual = Ctr::new(b"YELLOW SUBMARINE", &[0; 8], CounterLayout::BigEndian32);
+        assert_eq!(
+            BlockError::InvalidNonceLength { len: 8 },
+            actual.unwrap_err()
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::HashSet;
+
+/// Block sizes considered when the block size of a ciphertext is unknown
+pub const BLOCK_SIZES: [usize; 3] = [8, 16, 32];
+
+/// Mode of operation guessed from a ciphertext
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CipherMode {
+    Ecb,
+    Cbc,
+}
+
+/// Block repetition statistics of a ciphertext at its best block alignment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EcbScore {
+    pub block_size: usize,
+    /// Offset of the first block boundary, for ciphertexts with an unaligned prefix
+    pub offset: usize,
+    /// Number of blocks repeating an earlier block
+    pub repeats: usize,
+    pub blocks: usize,
+    /// Ratio of repeated blocks to all blocks
+    pub score: f32,
+}
+
+/// Count the blocks that repeat an earlier block
+pub fn duplicate_block_count(blocks: &[&[u8]]) -> usize {
+    let mut seen = HashSet::with_capacity(blocks.len());
+    blocks.iter().filter(|block| !seen.insert(*block)).count()
+}
+
+/// Score the block repetition of a ciphertext for a single block size
+///
+/// Every alignment of the block boundaries is tried, so that repeated blocks
+/// are found even if the ciphertext carries a prefix that isn't a whole number
+/// of blocks. Ties are broken by the lowest offset. A zero block size yields
+/// no blocks and a score of zero.
+pub fn ecb_score<T>(ciphertext: T, block_size: usize) -> EcbScore
+where
+    T: AsRef<[u8]>,
+{
+    let ciphertext = ciphertext.as_ref();
+    if block_size == 0 {
+        return EcbScore {
+            block_size,
+            offset: 0,
+            repeats: 0,
+            blocks: 0,
+            score: 0.0,
+        };
+    }
+    (0..block_size.min(ciphertext.len()).max(1))
+        .map(|offset| {
+            let blocks = ciphertext
+                .get(offset..)
+                .unwrap_or_default()
+                .chunks_exact(block_size)
+                .collect::<Vec<_>>();
+            let repeats = duplicate_block_count(&blocks);
+            EcbScore {
+                block_size,
+                offset,
+                repeats,
+                blocks: blocks.len(),
+                score: repeats as f32 / blocks.len().max(1) as f32,
+            }
+        })
+        .reduce(|best, s| if s.score > best.score { s } else { best })
+        .unwrap()
+}
+
+/// Score the block repetition of a ciphertext for every block size in [`BLOCK_SIZES`],
+/// returning the best score.
+///
+/// Both halves of a repeated block repeat as well, scoring the same as the
+/// block itself, so ties are broken by the largest block size.
+pub fn detect_ecb<T>(ciphertext: T) -> EcbScore
+where
+    T: AsRef<[u8]>,
+{
+    BLOCK_SIZES
+        .iter()
+        .map(|&block_size| ecb_score(&ciphertext, block_size))
+        .reduce(|best, s| if s.score >= best.score { s } else { best })
+        .unwrap()
+}
+
+/// Score every ciphertext in a slice of ciphertexts, returning the index of each
+/// ciphertext with its score ranked from most to least likely to be ECB-encrypted
+pub fn rank_ecb_candidates<T>(ciphertexts: &[T], block_size: usize) -> Vec<(usize, EcbScore)>
+where
+    T: AsRef<[u8]>,
+{
+    let mut scores = ciphertexts
+        .iter()
+        .map(|ciphertext| ecb_score(ciphertext, block_size))
+        .enumerate()
+        .collect::<Vec<_>>();
+    // Stable sort, so that ties are broken by the lowest index
+    scores.sort_by(|(_, a), (_, b)| b.score.total_cmp(&a.score));
+    scores
+}
+
+/// Guess the mode of an ECB or CBC encryption oracle using chosen plaintext
+///
+/// The oracle is fed three blocks of identical bytes, which always contain two
+/// aligned identical blocks regardless of any prefix the oracle prepends.
+/// Only ECB encrypts them to identical ciphertext blocks. A zero block size
+/// yields no blocks, so the oracle is reported as CBC without being queried.
+pub fn detect_oracle_mode<F>(mut oracle: F, block_size: usize) -> CipherMode
+where
+    F: FnMut(&[u8]) -> Vec<u8>,
+{
+    if block_size == 0 {
+        return CipherMode::Cbc;
+    }
+    let ciphertext = oracle(&vec![b'A'; 3 * block_size]);
+    let blocks = ciphertext.chunks_exact(block_size).collect::<Vec<_>>();
+    if duplicate_block_count(&blocks) > 0 {
+        CipherMode::Ecb
+    } else {
+        CipherMode::Cbc
+    }
+}
+
+#[cfg(test)]
+mod test_detect {
+    use super::{
+        detect_ecb, detect_oracle_mode, duplicate_block_count, ecb_score, rank_ecb_candidates,
+        CipherMode,
+    };
+    use crate::block::{cbc::Cbc, ecb::Ecb, BlockMode};
+
+    static PLAINTEXT: &[u8] = b"YELLOW SUBMARINEYELLOW SUBMARINEYELLOW SUBMARINE and some more";
+
+    #[test]
+    fn test_duplicate_block_count() {
+        let blocks: [&[u8]; 5] = [b"a", b"b", b"a", b"a", b"c"];
+        assert_eq!(2, duplicate_block_count(&blocks));
+        assert_eq!(0, duplicate_block_count(&[]));
+    }
+
+    #[test]
+    fn test_ecb_score() {
+        let ciphertext = Ecb::new(b"YELLOW SUBMARINE").unwrap().encrypt(PLAINTEXT);
+        let actual = ecb_score(&ciphertext, 16);
+        assert_eq!((0, 2, 4), (actual.offset, actual.repeats, actual.blocks));
+        assert_eq!(0.5, actual.score);
+        let ciphertext = Cbc::new(b"YELLOW SUBMARINE", &[0; 16])
+            .unwrap()
+            .encrypt(PLAINTEXT);
+        assert_eq!(0, ecb_score(&ciphertext, 16).repeats);
+    }
+
+    #[test]
+    fn test_ecb_score_unaligned() {
+        let mut ciphertext = b"header".to_vec();
+        ciphertext.extend(Ecb::new(b"YELLOW SUBMARINE").unwrap().encrypt(PLAINTEXT));
+        let actual = ecb_score(&ciphertext, 16);
+        assert_eq!(6, actual.offset);
+        assert_eq!(2, actual.repeats);
+    }
+
+    #[test]
+    fn test_ecb_score_short_input() {
+        assert_eq!(0, ecb_score(b"", 16).blocks);
+        assert_eq!(0.0, ecb_score(b"abc", 16).score);
+        let actual = ecb_score(b"aaaa", 0);
+        assert_eq!((0, 0, 0.0), (actual.repeats, actual.blocks, actual.score));
+    }
+
+    #[test]
+    fn test_detect_oracle_mode_zero_block_size() {
+        let ecb = Ecb::new(b"YELLOW SUBMARINE").unwrap();
+        assert_eq!(CipherMode::Cbc, detect_oracle_mode(|s| ecb.encrypt(s), 0));
+    }
+
+    #[test]
+    fn test_detect_ecb() {
+        let plaintext = [[b'x'; 16], [b'y'; 16], [b'x'; 16], [b'z'; 16]].concat();
+        let ecb = Ecb::new(&[1; 32]).unwrap();
+        let actual = detect_ecb(ecb.encrypt_blocks(&plaintext).unwrap());
+        assert_eq!(16, actual.block_size);
+        assert_eq!(1, actual.repeats);
+        assert_eq!(16, detect_ecb(ecb.encrypt(&[b'A'; 96])).block_size);
+        assert_eq!(0.0, detect_ecb((0..=255).collect::<Vec<u8>>()).score);
+    }
+
+    #[test]
+    fn test_rank_ecb_candidates() {
+        let ecb = Ecb::new(b"YELLOW SUBMARINE").unwrap();
+        let cbc = Cbc::new(b"YELLOW SUBMARINE", &[0; 16]).unwrap();
+        let ciphertexts = [
+            cbc.encrypt(PLAINTEXT),
+            ecb.encrypt(PLAINTEXT),
+            cbc.encrypt(b""),
+        ];
+        let actual = rank_ecb_candidates(&ciphertexts, 16);
+        assert_eq!(
+            vec![1, 0, 2],
+            actual.iter().map(|(i, _)| *i).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_detect_oracle_mode() {
+        let ecb = Ecb::new(b"YELLOW SUBMARINE").unwrap();
+        let cbc = Cbc::new(b"YELLOW SUBMARINE", &[0; 16]).unwrap();
+        for prefix_len in 0..=16 {
+            let prefix = vec![b'p'; prefix_len];
+            let oracle = |mode: &dyn BlockMode, s: &[u8]| mode.encrypt(&[&prefix[..], s].concat());
+            assert_eq!(CipherMode::Ecb, detect_oracle_mode(|s| oracle(&ecb, s), 16));
+            assert_eq!(CipherMode::Cbc, detect_oracle_mode(|s| oracle(&cbc, s), 16));
+        }
+    }
+}
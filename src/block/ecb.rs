@@ -0,0 +1,77 @@
+use super::{check_length, Aes, BlockError, BlockMode, BLOCK_SIZE};
+
+/// Electronic codebook mode, encrypting every block independently
+#[derive(Debug, Clone)]
+pub struct Ecb {
+    cipher: Aes,
+}
+
+impl Ecb {
+    /// Create an ECB mode cipher from a 16, 24 or 32-byte AES key
+    pub fn new(key: &[u8]) -> Result<Self, BlockError> {
+        Ok(Ecb {
+            cipher: Aes::new(key)?,
+        })
+    }
+}
+
+impl BlockMode for Ecb {
+    fn encrypt_blocks(&self, plaintext: &[u8]) -> Result<Vec<u8>, BlockError> {
+        check_length(plaintext)?;
+        let mut out = plaintext.to_vec();
+        for block in out.chunks_exact_mut(BLOCK_SIZE) {
+            self.cipher.encrypt_block(block.try_into().unwrap());
+        }
+        Ok(out)
+    }
+
+    fn decrypt_blocks(&self, ciphertext: &[u8]) -> Result<Vec<u8>, BlockError> {
+        check_length(ciphertext)?;
+        let mut out = ciphertext.to_vec();
+        for block in out.chunks_exact_mut(BLOCK_SIZE) {
+            self.cipher.decrypt_block(block.try_into().unwrap());
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod test_ecb {
+    use super::Ecb;
+    use crate::block::{BlockError, BlockMode};
+
+    #[test]
+    fn test_round_trip() {
+        for key_len in [16, 24, 32] {
+            let ecb = Ecb::new(&vec![0x42; key_len]).unwrap();
+            for len in 0..50 {
+                let plaintext = vec![b'x'; len];
+                let ciphertext = ecb.encrypt(&plaintext);
+                assert_eq!((len / 16 + 1) * 16, ciphertext.len());
+                assert_eq!(Ok(plaintext), ecb.decrypt(&ciphertext));
+            }
+        }
+    }
+
+    #[test]
+    fn test_identical_blocks() {
+        let ecb = Ecb::new(b"YELLOW SUBMARINE").unwrap();
+        let ciphertext = ecb.encrypt_blocks(&[b'A'; 32]).unwrap();
+        assert_eq!(ciphertext[..16], ciphertext[16..]);
+    }
+
+    #[test]
+    fn test_invalid_length() {
+        let ecb = Ecb::new(b"YELLOW SUBMARINE").unwrap();
+        let expected = BlockError::InvalidLength {
+            len: 17,
+            block_len: 16,
+        };
+        assert_eq!(Err(expected), ecb.encrypt_blocks(&[0; 17]));
+        assert_eq!(Err(expected), ecb.decrypt(&[0; 17]));
+        assert_eq!(
+            Err(BlockError::InvalidKeyLength { len: 5 }),
+            Ecb::new(b"short").map(|_| ())
+        );
+    }
+}
@@ -0,0 +1,177 @@
+use std::{error::Error, fmt};
+
+/// Error produced when padding or unpadding using PKCS#7
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaddingError {
+    /// PKCS#7 only supports block lengths of 1 to 255 bytes
+    InvalidBlockLength { block_len: usize },
+    /// The padded input is empty or isn't a whole number of blocks
+    UnalignedLength { len: usize, block_len: usize },
+    /// The last byte holds a padding length of zero or more than a block
+    InvalidPadLength { pad_len: u8 },
+    /// The padding byte at the given offset doesn't match the padding length
+    InvalidPadByte { offset: usize },
+}
+
+impl fmt::Display for PaddingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaddingError::InvalidBlockLength { block_len } => {
+                write!(f, "invalid block length {}", block_len)
+            }
+            PaddingError::UnalignedLength { len, block_len } => write!(
+                f,
+                "padded length {} is not a positive multiple of {}",
+                len, block_len
+            ),
+            PaddingError::InvalidPadLength { pad_len } => {
+                write!(f, "invalid padding length {}", pad_len)
+            }
+            PaddingError::InvalidPadByte { offset } => {
+                write!(f, "invalid padding byte at offset {}", offset)
+            }
+        }
+    }
+}
+
+impl Error for PaddingError {}
+
+fn check_block_len(block_len: usize) -> Result<u8, PaddingError> {
+    match u8::try_from(block_len) {
+        Ok(block_len) if block_len > 0 => Ok(block_len),
+        _ => Err(PaddingError::InvalidBlockLength { block_len }),
+    }
+}
+
+/// Pad a byte slice to a whole number of blocks using PKCS#7
+///
+/// A full block of padding is added if the input is already aligned.
+pub fn pad<T>(bytes: T, block_len: usize) -> Result<Vec<u8>, PaddingError>
+where
+    T: AsRef<[u8]>,
+{
+    let bytes = bytes.as_ref();
+    check_block_len(block_len)?;
+    let pad_len = block_len - bytes.len() % block_len;
+    let mut vec = Vec::with_capacity(bytes.len() + pad_len);
+    vec.extend(bytes);
+    vec.extend((0..pad_len).map(|_| pad_len as u8));
+    Ok(vec)
+}
+
+/// Validate the PKCS#7 padding of a byte slice, returning the unpadded length
+pub fn unpadded_len<T>(bytes: T, block_len: usize) -> Result<usize, PaddingError>
+where
+    T: AsRef<[u8]>,
+{
+    let bytes = bytes.as_ref();
+    let block_len_u8 = check_block_len(block_len)?;
+    if bytes.is_empty() || !bytes.len().is_multiple_of(block_len) {
+        return Err(PaddingError::UnalignedLength {
+            len: bytes.len(),
+            block_len,
+        });
+    }
+    let pad_len = bytes[bytes.len() - 1];
+    if pad_len == 0 || pad_len > block_len_u8 {
+        return Err(PaddingError::InvalidPadLength { pad_len });
+    }
+    let start = bytes.len() - pad_len as usize;
+    match bytes[start..].iter().position(|&b| b != pad_len) {
+        Some(i) => Err(PaddingError::InvalidPadByte { offset: start + i }),
+        None => Ok(start),
+    }
+}
+
+/// Strip the PKCS#7 padding from a byte slice
+pub fn unpad<T>(bytes: T, block_len: usize) -> Result<Vec<u8>, PaddingError>
+where
+    T: AsRef<[u8]>,
+{
+    let len = unpadded_len(&bytes, block_len)?;
+    Ok(bytes.as_ref()[..len].to_vec())
+}
+
+#[cfg(test)]
+mod test_padding {
+    use super::{pad, unpad, PaddingError};
+
+    #[test]
+    fn test_pad() {
+        assert_eq!(
+            Ok(b"YELLOW SUBMARINE\x04\x04\x04\x04".to_vec()),
+            pad("YELLOW SUBMARINE", 20)
+        );
+        assert_eq!(Ok(vec![16; 16]), pad([], 16));
+        let mut expected = vec![0; 20];
+        expected.extend([12; 12]);
+        assert_eq!(Ok(expected), pad([0; 20], 16));
+        assert_eq!(Ok(vec![1, 1]), pad([1], 1));
+    }
+
+    #[test]
+    fn test_invalid_block_length() {
+        for block_len in [0, 256, 1000] {
+            let expected = Err(PaddingError::InvalidBlockLength { block_len });
+            assert_eq!(expected, pad(b"abc", block_len));
+            assert_eq!(expected, unpad(b"abc", block_len));
+        }
+    }
+
+    #[test]
+    fn test_unpad_errors() {
+        assert_eq!(
+            Err(PaddingError::UnalignedLength {
+                len: 0,
+                block_len: 16
+            }),
+            unpad([], 16)
+        );
+        assert_eq!(
+            Err(PaddingError::UnalignedLength {
+                len: 15,
+                block_len: 16
+            }),
+            unpad([1; 15], 16)
+        );
+        assert_eq!(
+            Err(PaddingError::InvalidPadLength { pad_len: 0 }),
+            unpad([0; 16], 16)
+        );
+        assert_eq!(
+            Err(PaddingError::InvalidPadLength { pad_len: 17 }),
+            unpad([17; 16], 16)
+        );
+        assert_eq!(
+            Err(PaddingError::InvalidPadByte { offset: 11 }),
+            unpad(b"ICE ICE BABY\x05\x05\x05\x05", 16)
+        );
+    }
+
+    #[test]
+    fn test_round_trip_all_lengths() {
+        let input = (0..=255).collect::<Vec<u8>>();
+        for block_len in 1..=255 {
+            for len in 0..=255 {
+                let padded = pad(&input[..len], block_len).unwrap();
+                assert!(padded.len().is_multiple_of(block_len));
+                assert!(padded.len() > len && padded.len() <= len + block_len);
+                assert_eq!(Ok(&input[..len]), unpad(&padded, block_len).as_deref());
+            }
+        }
+    }
+
+    #[test]
+    fn test_unpad_rejects_corrupted_padding() {
+        for block_len in 1..=255 {
+            let mut padded = pad(b"", block_len).unwrap();
+            if block_len > 1 {
+                padded[0] ^= 1;
+                assert_eq!(
+                    Err(PaddingError::InvalidPadByte { offset: 0 }),
+                    unpad(&padded, block_len)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use std::{error::Error, fmt};
+
+pub mod base32;
+pub mod base64;
+pub mod base85;
+pub mod detect;
+pub mod hex;
+pub mod uu;
+
+/// Error produced when decoding malformed input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodecError {
+    /// A byte outside of the encoding alphabet was found at the given offset
+    InvalidCharacter { byte: u8, offset: usize },
+    /// The hex-encoded input has an odd number of digits
+    OddLength { len: usize },
+    /// Padding was found at the given offset where it is not allowed
+    InvalidPadding { offset: usize },
+    /// The input ended in the middle of a quantum
+    TruncatedQuantum { len: usize },
+    /// The character at the given offset carries non-zero bits that would be discarded
+    NonCanonicalTrailingBits { offset: usize },
+    /// The group starting at the given offset encodes a value that doesn't fit its octets
+    ValueOverflow { offset: usize },
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::InvalidCharacter { byte, offset } => {
+                write!(f, "invalid character {:#04x} at offset {}", byte, offset)
+            }
+            CodecError::OddLength { len } => write!(f, "odd input length {}", len),
+            CodecError::InvalidPadding { offset } => {
+                write!(f, "invalid padding at offset {}", offset)
+            }
+            CodecError::TruncatedQuantum { len } => {
+                write!(f, "input of {} symbols ends with a truncated quantum", len)
+            }
+            CodecError::NonCanonicalTrailingBits { offset } => {
+                write!(f, "non-zero trailing bits at offset {}", offset)
+            }
+            CodecError::ValueOverflow { offset } => {
+                write!(f, "group at offset {} overflows its octets", offset)
+            }
+        }
+    }
+}
+
+impl Error for CodecError {}
+
+/// Text encoding of binary data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Hex,
+    Base64(base64::Config),
+    Base32,
+    Base32Crockford,
+    Ascii85,
+    Z85,
+    Uuencode,
+}
+
+impl Encoding {
+    /// Encode raw bytes using this encoding
+    pub fn encode<T>(&self, bytes: T) -> Result<String, CodecError>
+    where
+        T: AsRef<[u8]>,
+    {
+        Ok(match self {
+            Encoding::Hex => hex::encode(bytes),
+            Encoding::Base64(config) => base64::encode_with(bytes, config),
+            Encoding::Base32 => base32::encode(bytes),
+            Encoding::Base32Crockford => base32::encode_crockford(bytes),
+            Encoding::Ascii85 => base85::encode(bytes),
+            Encoding::Z85 => base85::encode_z85(bytes)?,
+            Encoding::Uuencode => uu::encode(bytes),
+        })
+    }
+
+    /// Decode bytes in this encoding into raw bytes
+    pub fn decode<T>(&self, s: T) -> Result<Vec<u8>, CodecError>
+    where
+        T: AsRef<[u8]>,
+    {
+        match self {
+            Encoding::Hex => hex::decode(s),
+            Encoding::Base64(config) => base64::decode_with(s, config),
+            Encoding::Base32 => base32::decode(s),
+            Encoding::Base32Crockford => base32::decode_crockford(s),
+            Encoding::Ascii85 => base85::decode(s),
+            Encoding::Z85 => base85::decode_z85(s),
+            Encoding::Uuencode => uu::decode(s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_codec {
+    use super::{base64::Config, Encoding};
+    use crate::scoring::LetterFrequency;
+    use crate::set1::challenge3::crack_single_byte_xor_cipher;
+
+    static ENCODINGS: [Encoding; 8] = [
+        Encoding::Hex,
+        Encoding::Base64(Config::STANDARD),
+        Encoding::Base64(Config::URL_SAFE_NO_PAD),
+        Encoding::Base32,
+        Encoding::Base32Crockford,
+        Encoding::Ascii85,
+        Encoding::Z85,
+        Encoding::Uuencode,
+    ];
+
+    #[test]
+    fn test_round_trip_all_encodings() {
+        let input = (0..=u8::MAX).collect::<Vec<_>>();
+        for encoding in ENCODINGS {
+            let encoded = encoding.encode(&input).unwrap();
+            assert_eq!(input, encoding.decode(encoded).unwrap(), "{:?}", encoding);
+        }
+    }
+
+    #[test]
+    fn test_crack_any_encoding() {
+        let scorer = LetterFrequency::english();
+        let ciphertext = b"Cooking MC's like a pound of bacon"
+            .iter()
+            .map(|b| b ^ 88)
+            .collect::<Vec<_>>();
+        for encoding in ENCODINGS {
+            let Ok(encoded) = encoding.encode(&ciphertext) else {
+                continue;
+            };
+            let actual = crack_single_byte_xor_cipher(encoding.decode(encoded).unwrap(), &scorer);
+            assert_eq!(88_u8, actual.key, "{:?}", encoding);
+        }
+    }
+}
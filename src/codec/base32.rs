@@ -0,0 +1,246 @@
+use super::CodecError;
+
+/// RFC 4648 base32 alphabet
+static RFC4648_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Crockford base32 alphabet
+static CROCKFORD_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Get the 5-bit value of the specified RFC 4648 symbol, ignoring case
+fn rfc4648_value(c: u8) -> Option<u8> {
+    match c.to_ascii_uppercase() {
+        c @ b'A'..=b'Z' => Some(c - b'A'),
+        c @ b'2'..=b'7' => Some(c - b'2' + 26),
+        _ => None,
+    }
+}
+
+/// Get the 5-bit value of the specified Crockford symbol, ignoring case
+///
+/// The ambiguous letters `I`, `L` and `O` decode to their look-alike digits.
+fn crockford_value(c: u8) -> Option<u8> {
+    match c.to_ascii_uppercase() {
+        b'O' => Some(0),
+        b'I' | b'L' => Some(1),
+        c => CROCKFORD_ALPHABET
+            .iter()
+            .position(|&x| x == c)
+            .map(|x| x as u8),
+    }
+}
+
+/// Encode a byte slice into 5-bit symbols of the specified alphabet
+fn encode_symbols(bytes: &[u8], alphabet: &[u8; 32]) -> String {
+    let mut buf = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let mut acc = 0u16;
+    let mut bits = 0;
+    for &b in bytes {
+        acc = acc << 8 | b as u16;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            buf.push(alphabet[(acc >> bits & 0x1F) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        buf.push(alphabet[(acc << (5 - bits) & 0x1F) as usize] as char);
+    }
+    buf
+}
+
+/// Decode `(offset, value)` pairs of 5-bit symbols into raw bytes
+fn decode_symbols(values: &[(usize, u8)]) -> Result<Vec<u8>, CodecError> {
+    // Only these symbol counts in the final group describe a whole number of octets
+    if matches!(values.len() % 8, 1 | 3 | 6) {
+        return Err(CodecError::TruncatedQuantum { len: values.len() });
+    }
+    let mut buf = Vec::with_capacity(values.len() * 5 / 8);
+    let mut acc = 0u16;
+    let mut bits = 0;
+    for &(_, value) in values {
+        acc = acc << 5 | value as u16;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            buf.push((acc >> bits) as u8);
+        }
+    }
+    if acc & ((1 << bits) - 1) != 0 {
+        let (offset, _) = values[values.len() - 1];
+        return Err(CodecError::NonCanonicalTrailingBits { offset });
+    }
+    Ok(buf)
+}
+
+/// Encode a byte slice into a padded RFC 4648 base32-encoded string
+pub fn encode<T>(bytes: T) -> String
+where
+    T: AsRef<[u8]>,
+{
+    let mut buf = encode_symbols(bytes.as_ref(), RFC4648_ALPHABET);
+    while !buf.len().is_multiple_of(8) {
+        buf.push('=');
+    }
+    buf
+}
+
+/// Decode a padded RFC 4648 base32-encoded byte slice into raw bytes
+///
+/// Symbols are accepted in either case and ASCII whitespace is ignored.
+pub fn decode<T>(s: T) -> Result<Vec<u8>, CodecError>
+where
+    T: AsRef<[u8]>,
+{
+    let mut values = Vec::new();
+    let mut pad_offset = None;
+    let mut symbols = 0usize;
+    for (offset, &byte) in s.as_ref().iter().enumerate() {
+        if byte.is_ascii_whitespace() {
+            continue;
+        }
+        symbols += 1;
+        match (byte, rfc4648_value(byte), pad_offset) {
+            (b'=', _, None) => pad_offset = Some(offset),
+            (b'=', _, Some(_)) => {}
+            (_, Some(_), Some(pad_offset)) => {
+                return Err(CodecError::InvalidPadding { offset: pad_offset })
+            }
+            (_, Some(value), None) => values.push((offset, value)),
+            (_, None, _) => return Err(CodecError::InvalidCharacter { byte, offset }),
+        }
+    }
+    // The padding has to complete the final group, which must contain data
+    match pad_offset {
+        Some(offset) if !symbols.is_multiple_of(8) || symbols - values.len() > 6 => {
+            Err(CodecError::InvalidPadding { offset })
+        }
+        None if !values.len().is_multiple_of(8) => {
+            Err(CodecError::TruncatedQuantum { len: symbols })
+        }
+        _ => decode_symbols(&values),
+    }
+}
+
+/// Encode a byte slice into an unpadded Crockford base32-encoded string
+pub fn encode_crockford<T>(bytes: T) -> String
+where
+    T: AsRef<[u8]>,
+{
+    encode_symbols(bytes.as_ref(), CROCKFORD_ALPHABET)
+}
+
+/// Decode an unpadded Crockford base32-encoded byte slice into raw bytes
+///
+/// Symbols are accepted in either case, ambiguous letters are mapped to digits,
+/// and hyphens as well as ASCII whitespace are ignored.
+pub fn decode_crockford<T>(s: T) -> Result<Vec<u8>, CodecError>
+where
+    T: AsRef<[u8]>,
+{
+    let values = s
+        .as_ref()
+        .iter()
+        .enumerate()
+        .filter(|(_, &b)| b != b'-' && !b.is_ascii_whitespace())
+        .map(|(offset, &byte)| match crockford_value(byte) {
+            Some(value) => Ok((offset, value)),
+            None => Err(CodecError::InvalidCharacter { byte, offset }),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    decode_symbols(&values)
+}
+
+#[cfg(test)]
+mod test_codec_base32 {
+    use super::{decode, decode_crockford, encode, encode_crockford};
+    use crate::codec::CodecError;
+
+    /// Test vectors from RFC 4648 section 10
+    static RFC4648_VECTORS: [(&str, &str); 7] = [
+        ("", ""),
+        ("f", "MY======"),
+        ("fo", "MZXQ===="),
+        ("foo", "MZXW6==="),
+        ("foob", "MZXW6YQ="),
+        ("fooba", "MZXW6YTB"),
+        ("foobar", "MZXW6YTBOI======"),
+    ];
+
+    #[test]
+    fn test_encode() {
+        for (input, expected) in RFC4648_VECTORS {
+            assert_eq!(expected, encode(input));
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        for (expected, input) in RFC4648_VECTORS {
+            assert_eq!(expected.as_bytes(), decode(input).unwrap());
+        }
+        assert_eq!(b"foobar".to_vec(), decode("mzxw6ytb\noi======").unwrap());
+    }
+
+    #[test]
+    fn test_decode_malformed_input() {
+        let expected = CodecError::InvalidCharacter {
+            byte: b'1',
+            offset: 2,
+        };
+        assert_eq!(Err(expected), decode("MZ1W6==="));
+        assert_eq!(
+            Err(CodecError::InvalidPadding { offset: 1 }),
+            decode("M=======")
+        );
+        assert_eq!(
+            Err(CodecError::InvalidPadding { offset: 2 }),
+            decode("MY=====A")
+        );
+        assert_eq!(
+            Err(CodecError::InvalidPadding { offset: 2 }),
+            decode("MY=====")
+        );
+        assert_eq!(Err(CodecError::TruncatedQuantum { len: 2 }), decode("MY"));
+        assert_eq!(
+            Err(CodecError::TruncatedQuantum { len: 3 }),
+            decode("MZX=====")
+        );
+        let expected = CodecError::NonCanonicalTrailingBits { offset: 1 };
+        assert_eq!(Err(expected), decode("MZ======"));
+    }
+
+    #[test]
+    fn test_crockford() {
+        let input = b"Hello world";
+        let encoded = encode_crockford(input);
+        assert_eq!("91JPRV3F41VPYWKCCG", encoded);
+        assert_eq!(input.to_vec(), decode_crockford(&encoded).unwrap());
+        assert_eq!(
+            input.to_vec(),
+            decode_crockford("9IjprV3f-4lVPywkccg").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_crockford_malformed_input() {
+        let expected = CodecError::InvalidCharacter {
+            byte: b'U',
+            offset: 1,
+        };
+        assert_eq!(Err(expected), decode_crockford("9U"));
+        assert_eq!(
+            Err(CodecError::TruncatedQuantum { len: 3 }),
+            decode_crockford("91J")
+        );
+    }
+
+    #[test]
+    fn test_round_trip_all_lengths() {
+        let input = (0..=u8::MAX).rev().collect::<Vec<_>>();
+        for len in 0..=20 {
+            let input = &input[..len];
+            assert_eq!(input, decode(encode(input)).unwrap());
+            assert_eq!(input, decode_crockford(encode_crockford(input)).unwrap());
+        }
+    }
+}
@@ -0,0 +1,471 @@
+use super::CodecError;
+
+pub mod stream;
+
+/// Marker for bytes that are not part of an alphabet in the reverse lookup table
+const INVALID: u8 = 0xFF;
+
+/// Base64 alphabet with a precomputed reverse lookup table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Alphabet {
+    symbols: [u8; 64],
+    values: [u8; 256],
+}
+
+impl Alphabet {
+    /// Standard alphabet (RFC 4648 section 4)
+    pub const STANDARD: Alphabet =
+        Alphabet::new(b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/");
+
+    /// URL- and filename-safe alphabet (RFC 4648 section 5)
+    pub const URL_SAFE: Alphabet =
+        Alphabet::new(b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_");
+
+    /// Create an alphabet from 64 distinct printable ASCII symbols, excluding `=`
+    ///
+    /// Panics if the symbols don't form a valid alphabet.
+    pub const fn new(symbols: &[u8; 64]) -> Alphabet {
+        Alphabet::try_new(symbols).expect("invalid base64 alphabet")
+    }
+
+    /// Create an alphabet from 64 distinct printable ASCII symbols, excluding `=`
+    pub const fn try_new(symbols: &[u8; 64]) -> Option<Alphabet> {
+        let mut values = [INVALID; 256];
+        let mut i = 0;
+        while i < 64 {
+            let c = symbols[i];
+            if !c.is_ascii_graphic() || c == b'=' || values[c as usize] != INVALID {
+                return None;
+            }
+            values[c as usize] = i as u8;
+            i += 1;
+        }
+        Some(Alphabet {
+            symbols: *symbols,
+            values,
+        })
+    }
+
+    /// Get the symbol encoding the specified 6-bit value
+    pub fn symbol(&self, value: u8) -> u8 {
+        self.symbols[(value & 0x3F) as usize]
+    }
+
+    /// Get the 6-bit value of the specified symbol
+    pub fn value(&self, symbol: u8) -> Option<u8> {
+        match self.values[symbol as usize] {
+            INVALID => None,
+            value => Some(value),
+        }
+    }
+}
+
+/// How padding is treated when decoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaddingMode {
+    /// Padding must be present if and only if the config emits it
+    Strict,
+    /// Padding is optional, but must be correct if present
+    Lenient,
+}
+
+/// Base64 encoding and decoding options
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    pub alphabet: &'static Alphabet,
+    /// Whether encoded output is padded with `=`
+    pub pad: bool,
+    pub padding_mode: PaddingMode,
+}
+
+impl Config {
+    /// Standard alphabet with padding
+    pub const STANDARD: Config = Config::new(&Alphabet::STANDARD);
+    /// Standard alphabet without padding
+    pub const STANDARD_NO_PAD: Config = Config::STANDARD.with_padding(false);
+    /// URL-safe alphabet with padding
+    pub const URL_SAFE: Config = Config::new(&Alphabet::URL_SAFE);
+    /// URL-safe alphabet without padding, as used by JWTs
+    pub const URL_SAFE_NO_PAD: Config = Config::URL_SAFE.with_padding(false);
+
+    /// Create a config with strict padding for the specified alphabet
+    pub const fn new(alphabet: &'static Alphabet) -> Config {
+        Config {
+            alphabet,
+            pad: true,
+            padding_mode: PaddingMode::Strict,
+        }
+    }
+
+    pub const fn with_padding(self, pad: bool) -> Config {
+        Config { pad, ..self }
+    }
+
+    pub const fn with_padding_mode(self, padding_mode: PaddingMode) -> Config {
+        Config {
+            padding_mode,
+            ..self
+        }
+    }
+
+    /// Whether a decoded quantum must be completed by padding
+    fn requires_padding(&self) -> bool {
+        self.pad && self.padding_mode == PaddingMode::Strict
+    }
+
+    /// Whether padding is allowed when decoding
+    fn allows_padding(&self) -> bool {
+        self.pad || self.padding_mode == PaddingMode::Lenient
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::STANDARD
+    }
+}
+
+/// Encode up to three octets into base64 symbols
+///
+/// Returns the symbols along with the number of symbols to emit.
+fn encode_chunk(chunk: &[u8], config: &Config) -> ([u8; 4], usize) {
+    let triple = chunk
+        .iter()
+        .enumerate()
+        .fold(0u32, |acc, (i, &b)| acc | (b as u32) << (16 - 8 * i));
+    let mut symbols = [b'='; 4];
+    for (i, c) in symbols.iter_mut().enumerate().take(chunk.len() + 1) {
+        *c = config.alphabet.symbol((triple >> (18 - 6 * i)) as u8);
+    }
+    let len = if config.pad { 4 } else { chunk.len() + 1 };
+    (symbols, len)
+}
+
+/// Encode a byte slice into a standard base64-encoded string
+pub fn encode<T>(bytes: T) -> String
+where
+    T: AsRef<[u8]>,
+{
+    encode_with(bytes, &Config::STANDARD)
+}
+
+/// Encode a byte slice into a base64-encoded string using the specified config
+pub fn encode_with<T>(bytes: T, config: &Config) -> String
+where
+    T: AsRef<[u8]>,
+{
+    let bytes = bytes.as_ref();
+    let mut buf = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let (symbols, len) = encode_chunk(chunk, config);
+        buf.extend(symbols[..len].iter().map(|&c| c as char));
+    }
+    buf
+}
+
+/// Incremental base64 decoder shared by `decode_with` and `stream::Base64Reader`
+#[derive(Debug)]
+struct Decoder {
+    config: Config,
+    /// 6-bit values of the current quantum
+    quantum: [u8; 4],
+    /// Number of data symbols in the current quantum
+    len: usize,
+    /// Offset of the most recent data symbol
+    last_offset: usize,
+    /// Offset of the first padding character, if any
+    pad_offset: Option<usize>,
+    /// Number of padding characters seen so far
+    pad_len: usize,
+    /// Number of significant (non-whitespace) symbols seen so far
+    symbols: usize,
+    /// Whether the padded final quantum has been completed
+    done: bool,
+}
+
+impl Decoder {
+    fn new(config: Config) -> Self {
+        Decoder {
+            config,
+            quantum: [0; 4],
+            len: 0,
+            last_offset: 0,
+            pad_offset: None,
+            pad_len: 0,
+            symbols: 0,
+            done: false,
+        }
+    }
+
+    /// Feed a single input byte found at the given offset into the decoder
+    fn push(&mut self, offset: usize, byte: u8, out: &mut Vec<u8>) -> Result<(), CodecError> {
+        if byte.is_ascii_whitespace() {
+            return Ok(());
+        }
+        self.symbols += 1;
+        let value = self.config.alphabet.value(byte);
+        if let Some(pad_offset) = self.pad_offset {
+            // Only more padding may follow padding, and only until the quantum is complete
+            return match (byte, value) {
+                (b'=', _) if !self.done => self.push_padding(offset, out),
+                (b'=', _) | (_, Some(_)) => Err(CodecError::InvalidPadding { offset: pad_offset }),
+                (_, None) => Err(CodecError::InvalidCharacter { byte, offset }),
+            };
+        }
+        if byte == b'=' {
+            return self.push_padding(offset, out);
+        }
+        self.quantum[self.len] = value.ok_or(CodecError::InvalidCharacter { byte, offset })?;
+        self.len += 1;
+        self.last_offset = offset;
+        if self.len == 4 {
+            self.flush(out)?;
+        }
+        Ok(())
+    }
+
+    /// Feed a padding character found at the given offset into the decoder
+    fn push_padding(&mut self, offset: usize, out: &mut Vec<u8>) -> Result<(), CodecError> {
+        // A quantum needs at least two data symbols to encode a full octet
+        if self.len < 2 || !self.config.allows_padding() {
+            return Err(CodecError::InvalidPadding {
+                offset: self.pad_offset.unwrap_or(offset),
+            });
+        }
+        self.pad_offset.get_or_insert(offset);
+        self.pad_len += 1;
+        if self.len + self.pad_len == 4 {
+            self.flush(out)?;
+            self.done = true;
+        }
+        Ok(())
+    }
+
+    /// Decode the current quantum into `out`
+    fn flush(&mut self, out: &mut Vec<u8>) -> Result<(), CodecError> {
+        let n = self.quantum[..self.len]
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, &x)| acc | (x as u32) << (18 - 6 * i));
+        let len = self.len * 6 / 8;
+        // Bits that don't make up a full octet must be zero
+        if n & (0xFF_FF_FF >> (8 * len)) != 0 {
+            return Err(CodecError::NonCanonicalTrailingBits {
+                offset: self.last_offset,
+            });
+        }
+        out.extend(&[(n >> 16) as u8, (n >> 8) as u8, n as u8][..len]);
+        self.len = 0;
+        Ok(())
+    }
+
+    /// Signal the end of the input, decoding an unpadded final quantum into `out`
+    fn finish(&mut self, out: &mut Vec<u8>) -> Result<(), CodecError> {
+        match self.pad_offset {
+            Some(offset) if !self.done => Err(CodecError::InvalidPadding { offset }),
+            None if self.len == 1 || (self.len != 0 && self.config.requires_padding()) => {
+                Err(CodecError::TruncatedQuantum { len: self.symbols })
+            }
+            None if self.len != 0 => self.flush(out),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Decode a standard base64-encoded byte slice into raw bytes
+///
+/// ASCII whitespace (e.g. line breaks in wrapped input) is ignored. Error offsets
+/// refer to positions in the original input, whitespace included.
+pub fn decode<T>(s: T) -> Result<Vec<u8>, CodecError>
+where
+    T: AsRef<[u8]>,
+{
+    decode_with(s, &Config::STANDARD)
+}
+
+/// Decode a base64-encoded byte slice into raw bytes using the specified config
+pub fn decode_with<T>(s: T, config: &Config) -> Result<Vec<u8>, CodecError>
+where
+    T: AsRef<[u8]>,
+{
+    let s = s.as_ref();
+    let mut decoder = Decoder::new(*config);
+    let mut buf = Vec::with_capacity(s.len() / 4 * 3 + 2);
+    for (offset, &byte) in s.iter().enumerate() {
+        decoder.push(offset, byte, &mut buf)?;
+    }
+    decoder.finish(&mut buf)?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod test_codec_base64 {
+    use super::{decode, decode_with, encode, encode_with, Alphabet, Config, PaddingMode};
+    use crate::codec::CodecError;
+
+    #[test]
+    fn test_encode() {
+        assert_eq!("", encode(b""));
+        assert_eq!("SA==", encode(b"H"));
+        assert_eq!("SGk=", encode(b"Hi"));
+        assert_eq!("SGVsbG8gd29ybGQ=", encode(b"Hello world"));
+    }
+
+    #[test]
+    fn test_decode() {
+        let input = "SGVsbG8gd29ybGQ=";
+        let expected = b"Hello world";
+        let actual = decode(input).unwrap();
+        assert_eq!(&expected[..], actual);
+    }
+
+    #[test]
+    fn test_decode_ignores_line_breaks() {
+        let input = "SGVsbG8g\nd29y\r\nbGQ=\n";
+        let expected = b"Hello world";
+        let actual = decode(input).unwrap();
+        assert_eq!(&expected[..], actual);
+    }
+
+    #[test]
+    fn test_decode_unpadded_single_byte() {
+        assert_eq!(Ok(b"H".to_vec()), decode("SA=="));
+        assert_eq!(Err(CodecError::InvalidPadding { offset: 2 }), decode("SA="));
+    }
+
+    #[test]
+    fn test_decode_invalid_character() {
+        let expected = CodecError::InvalidCharacter {
+            byte: b'*',
+            offset: 3,
+        };
+        assert_eq!(Err(expected), decode("SG\n*="));
+    }
+
+    #[test]
+    fn test_decode_truncated_quantum() {
+        assert_eq!(Err(CodecError::TruncatedQuantum { len: 3 }), decode("SGk"));
+        assert_eq!(
+            Err(CodecError::TruncatedQuantum { len: 5 }),
+            decode("SGVsb")
+        );
+    }
+
+    #[test]
+    fn test_decode_invalid_padding() {
+        assert_eq!(
+            Err(CodecError::InvalidPadding { offset: 2 }),
+            decode("SA==SGk=")
+        );
+        assert_eq!(
+            Err(CodecError::InvalidPadding { offset: 1 }),
+            decode("S===")
+        );
+        assert_eq!(
+            Err(CodecError::InvalidPadding { offset: 3 }),
+            decode("SGk==")
+        );
+        let expected = CodecError::InvalidCharacter {
+            byte: b'*',
+            offset: 3,
+        };
+        assert_eq!(Err(expected), decode("SA=*"));
+    }
+
+    #[test]
+    fn test_decode_non_canonical_trailing_bits() {
+        let expected = CodecError::NonCanonicalTrailingBits { offset: 2 };
+        assert_eq!(Err(expected), decode("SGl="));
+        let expected = CodecError::NonCanonicalTrailingBits { offset: 1 };
+        assert_eq!(Err(expected), decode("SB=="));
+    }
+
+    #[test]
+    fn test_round_trip_binary() {
+        let input = [0x00, 0x7f, 0x80, 0xfe, 0xff];
+        let actual = decode(encode(input)).unwrap();
+        assert_eq!(&input[..], actual);
+    }
+
+    #[test]
+    fn test_url_safe() {
+        let input = [0xfb, 0xff, 0xbf];
+        assert_eq!("+/+/", encode(input));
+        assert_eq!("-_-_", encode_with(input, &Config::URL_SAFE));
+        assert_eq!(Ok(input.to_vec()), decode_with("-_-_", &Config::URL_SAFE));
+        let expected = CodecError::InvalidCharacter {
+            byte: b'+',
+            offset: 0,
+        };
+        assert_eq!(Err(expected), decode_with("+/+/", &Config::URL_SAFE));
+    }
+
+    #[test]
+    fn test_no_pad() {
+        assert_eq!("SA", encode_with(b"H", &Config::STANDARD_NO_PAD));
+        assert_eq!("SGk", encode_with(b"Hi", &Config::STANDARD_NO_PAD));
+        assert_eq!(
+            Ok(b"Hi".to_vec()),
+            decode_with("SGk", &Config::STANDARD_NO_PAD)
+        );
+        let expected = CodecError::InvalidPadding { offset: 3 };
+        assert_eq!(Err(expected), decode_with("SGk=", &Config::STANDARD_NO_PAD));
+        let expected = CodecError::TruncatedQuantum { len: 5 };
+        assert_eq!(
+            Err(expected),
+            decode_with("SGVsb", &Config::STANDARD_NO_PAD)
+        );
+    }
+
+    #[test]
+    fn test_lenient_padding() {
+        let config = Config::URL_SAFE.with_padding_mode(PaddingMode::Lenient);
+        assert_eq!(Ok(b"Hi".to_vec()), decode_with("SGk", &config));
+        assert_eq!(Ok(b"Hi".to_vec()), decode_with("SGk=", &config));
+        let expected = CodecError::InvalidPadding { offset: 3 };
+        assert_eq!(Err(expected), decode_with("SGk==", &config));
+        let config = config.with_padding(false);
+        assert_eq!(Ok(b"Hi".to_vec()), decode_with("SGk=", &config));
+    }
+
+    #[test]
+    fn test_jwt_header() {
+        let input = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9";
+        let expected = br#"{"alg":"HS256","typ":"JWT"}"#;
+        let actual = decode_with(input, &Config::URL_SAFE_NO_PAD).unwrap();
+        assert_eq!(&expected[..], actual);
+        assert_eq!(input, encode_with(actual, &Config::URL_SAFE_NO_PAD));
+    }
+
+    #[test]
+    fn test_custom_alphabet() {
+        static ALPHABET: Alphabet =
+            Alphabet::new(b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz");
+        let config = Config::new(&ALPHABET).with_padding(false);
+        let input = (0..=u8::MAX).collect::<Vec<_>>();
+        let encoded = encode_with(&input, &config);
+        assert!(encoded.starts_with(".."));
+        assert_eq!(Ok(input), decode_with(encoded, &config));
+    }
+
+    #[test]
+    fn test_invalid_alphabet() {
+        let mut symbols = *b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        symbols[63] = b'+';
+        assert!(Alphabet::try_new(&symbols).is_none());
+        symbols[63] = b'=';
+        assert!(Alphabet::try_new(&symbols).is_none());
+        symbols[63] = b' ';
+        assert!(Alphabet::try_new(&symbols).is_none());
+    }
+
+    #[test]
+    fn test_reverse_table() {
+        for value in 0..64 {
+            let symbol = Alphabet::STANDARD.symbol(value);
+            assert_eq!(Some(value), Alphabet::STANDARD.value(symbol));
+        }
+        assert_eq!(None, Alphabet::STANDARD.value(b'-'));
+        assert_eq!(None, Alphabet::STANDARD.value(0xff));
+    }
+}
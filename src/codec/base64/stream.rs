@@ -0,0 +1,430 @@
+use std::io::{self, Read, Write};
+
+use super::{encode_chunk, Config, Decoder};
+use crate::codec::CodecError;
+
+/// Size of the chunks read from the underlying reader
+const READ_CHUNK_SIZE: usize = 4096;
+
+/// Line wrapping applied to base64 output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineWrap {
+    /// Maximum number of characters per line
+    width: usize,
+    /// Line ending inserted between lines
+    ending: &'static [u8],
+}
+
+impl LineWrap {
+    /// Create a line wrapping with the given width, or `None` if the width is zero
+    pub fn new(width: usize, ending: &'static [u8]) -> Option<Self> {
+        (width > 0).then_some(LineWrap { width, ending })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn ending(&self) -> &'static [u8] {
+        self.ending
+    }
+
+    /// MIME line wrapping (RFC 2045): 76 columns, CRLF line endings
+    pub const MIME: LineWrap = LineWrap {
+        width: 76,
+        ending: b"\r\n",
+    };
+
+    /// PEM line wrapping (RFC 7468): 64 columns, LF line endings
+    pub const PEM: LineWrap = LineWrap {
+        width: 64,
+        ending: b"\n",
+    };
+}
+
+/// Adapter that decodes base64 read from an underlying reader
+///
+/// Whitespace in the input is skipped. Malformed input is reported as an
+/// `io::ErrorKind::InvalidData` error wrapping a `CodecError`.
+pub struct Base64Reader<R: Read> {
+    inner: R,
+    decoder: Decoder,
+    /// Offset of the next byte read from the underlying reader
+    offset: usize,
+    /// Decoded bytes not yet handed out to the caller
+    buf: Vec<u8>,
+    pos: usize,
+    eof: bool,
+}
+
+impl<R: Read> Base64Reader<R> {
+    pub fn new(inner: R) -> Self {
+        Base64Reader::with_config(inner, Config::STANDARD)
+    }
+
+    pub fn with_config(inner: R, config: Config) -> Self {
+        Base64Reader {
+            inner,
+            decoder: Decoder::new(config),
+            offset: 0,
+            buf: Vec::with_capacity(READ_CHUNK_SIZE / 4 * 3 + 3),
+            pos: 0,
+            eof: false,
+        }
+    }
+
+    /// Unwrap the adapter, returning the underlying reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Read and decode the next chunk of input into the internal buffer
+    fn fill_buf(&mut self) -> io::Result<()> {
+        let mut chunk = [0u8; READ_CHUNK_SIZE];
+        let n = loop {
+            match self.inner.read(&mut chunk) {
+                Ok(n) => break n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        };
+        self.buf.clear();
+        self.pos = 0;
+        if n == 0 {
+            self.eof = true;
+            return self.decoder.finish(&mut self.buf).map_err(invalid_data);
+        }
+        for &byte in &chunk[..n] {
+            self.decoder
+                .push(self.offset, byte, &mut self.buf)
+                .map_err(invalid_data)?;
+            self.offset += 1;
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for Base64Reader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.buf.len() && !self.eof {
+            self.fill_buf()?;
+        }
+        let len = out.len().min(self.buf.len() - self.pos);
+        out[..len].copy_from_slice(&self.buf[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+/// Adapter that base64-encodes everything written to it into an underlying writer
+///
+/// The final partial quantum is only written by `finish` (or on drop, ignoring errors).
+pub struct Base64Writer<W: Write> {
+    inner: Option<W>,
+    config: Config,
+    wrap: Option<LineWrap>,
+    /// Input bytes that don't make up a full triple yet
+    pending: Vec<u8>,
+    /// Number of characters written on the current line
+    column: usize,
+}
+
+impl<W: Write> Base64Writer<W> {
+    pub fn new(inner: W) -> Self {
+        Base64Writer::with_options(inner, Config::STANDARD, None)
+    }
+
+    /// Create a writer that wraps its output into lines
+    pub fn with_line_wrap(inner: W, wrap: LineWrap) -> Self {
+        Base64Writer::with_options(inner, Config::STANDARD, Some(wrap))
+    }
+
+    pub fn with_options(inner: W, config: Config, wrap: Option<LineWrap>) -> Self {
+        Base64Writer {
+            inner: Some(inner),
+            config,
+            wrap,
+            pending: Vec::with_capacity(3),
+            column: 0,
+        }
+    }
+
+    /// Encode the given triples into `out`, inserting line endings as needed,
+    /// and return the column reached
+    ///
+    /// The writer state is left untouched, so that it can be updated only once
+    /// the output was written successfully.
+    fn encode_into(&self, bytes: &[u8], mut column: usize, out: &mut Vec<u8>) -> usize {
+        for chunk in bytes.chunks(3) {
+            let (symbols, len) = encode_chunk(chunk, &self.config);
+            for &c in &symbols[..len] {
+                if let Some(wrap) = self.wrap {
+                    if column == wrap.width {
+                        out.extend(wrap.ending);
+                        column = 0;
+                    }
+                }
+                out.push(c);
+                column += 1;
+            }
+        }
+        column
+    }
+
+    /// Write the final quantum and flush, returning the underlying writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_final()?;
+        Ok(self.inner.take().unwrap())
+    }
+
+    fn write_final(&mut self) -> io::Result<()> {
+        let mut out = Vec::with_capacity(4 + 2);
+        let column = self.encode_into(&self.pending, self.column, &mut out);
+        self.inner.as_mut().unwrap().write_all(&out)?;
+        self.pending.clear();
+        self.column = column;
+        self.inner.as_mut().unwrap().flush()
+    }
+}
+
+impl<W: Write> Write for Base64Writer<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Complete a pending triple first, then encode all full triples of the input
+        let fill = buf.len().min(3 - self.pending.len());
+        if self.pending.len() + fill < 3 {
+            self.pending.extend(buf);
+            return Ok(buf.len());
+        }
+        let mut triple = self.pending.clone();
+        triple.extend(&buf[..fill]);
+        let rest = &buf[fill..];
+        let full = rest.len() - rest.len() % 3;
+        let mut out = Vec::with_capacity((3 + full) / 3 * 4 * 2);
+        let column = self.encode_into(&triple, self.column, &mut out);
+        let column = self.encode_into(&rest[..full], column, &mut out);
+        // On failure none of the input counts as written, so keep the state as it was
+        self.inner.as_mut().unwrap().write_all(&out)?;
+        self.pending.clear();
+        self.pending.extend(&rest[full..]);
+        self.column = column;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.as_mut().unwrap().flush()
+    }
+}
+
+impl<W: Write> Drop for Base64Writer<W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            let _ = self.write_final();
+        }
+    }
+}
+
+/// Wrap a codec error into an I/O error
+fn invalid_data(e: CodecError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+#[cfg(test)]
+mod test_codec_base64_stream {
+    use std::io::{Read, Write};
+
+    use super::{Base64Reader, Base64Writer, LineWrap};
+    use crate::codec::{
+        base64::{self, Config},
+        CodecError,
+    };
+    use crate::test_util::pseudo_random_bytes;
+
+    /// Reader that hands out at most one byte per call
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(self.0.len()).min(1);
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn test_reader() {
+        let input = "SGVs\nbG8g\r\nd29y bGQ=\n";
+        let mut actual = Vec::new();
+        Base64Reader::new(input.as_bytes())
+            .read_to_end(&mut actual)
+            .unwrap();
+        assert_eq!(&b"Hello world"[..], actual);
+    }
+
+    #[test]
+    fn test_reader_one_byte_at_a_time() {
+        let input = "SGVsbG8gd29ybGQ=";
+        let mut reader = Base64Reader::new(Trickle(input.as_bytes()));
+        let mut actual = Vec::new();
+        let mut byte = [0u8; 1];
+        while reader.read(&mut byte).unwrap() == 1 {
+            actual.push(byte[0]);
+        }
+        assert_eq!(&b"Hello world"[..], actual);
+    }
+
+    #[test]
+    fn test_reader_data_file() {
+        let input = include_bytes!("../../data/s1c6.txt");
+        let expected = base64::decode(input).unwrap();
+        let mut actual = Vec::new();
+        Base64Reader::new(&input[..])
+            .read_to_end(&mut actual)
+            .unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_reader_malformed_input() {
+        let input = "SGVs\nbG*g";
+        let err = Base64Reader::new(input.as_bytes())
+            .read_to_end(&mut Vec::new())
+            .unwrap_err();
+        assert_eq!(std::io::ErrorKind::InvalidData, err.kind());
+        let expected = CodecError::InvalidCharacter {
+            byte: b'*',
+            offset: 7,
+        };
+        assert_eq!(Some(&expected), err.into_inner().unwrap().downcast_ref());
+    }
+
+    #[test]
+    fn test_reader_truncated_input() {
+        let err = Base64Reader::new("SGVsb".as_bytes())
+            .read_to_end(&mut Vec::new())
+            .unwrap_err();
+        let expected = CodecError::TruncatedQuantum { len: 5 };
+        assert_eq!(Some(&expected), err.into_inner().unwrap().downcast_ref());
+    }
+
+    #[test]
+    fn test_writer() {
+        let mut writer = Base64Writer::new(Vec::new());
+        for chunk in b"Hello world".chunks(2) {
+            writer.write_all(chunk).unwrap();
+        }
+        let actual = writer.finish().unwrap();
+        assert_eq!(&b"SGVsbG8gd29ybGQ="[..], actual);
+    }
+
+    #[test]
+    fn test_writer_finishes_on_drop() {
+        let mut actual = Vec::new();
+        {
+            let mut writer = Base64Writer::new(&mut actual);
+            writer.write_all(b"Hi").unwrap();
+        }
+        assert_eq!(&b"SGk="[..], actual);
+    }
+
+    #[test]
+    fn test_writer_mime_line_wrap() {
+        let input = [0xA5u8; 100];
+        let mut writer = Base64Writer::with_line_wrap(Vec::new(), LineWrap::MIME);
+        writer.write_all(&input).unwrap();
+        let actual = String::from_utf8(writer.finish().unwrap()).unwrap();
+        let lines = actual.split("\r\n").collect::<Vec<_>>();
+        assert_eq!(
+            vec![76, 60],
+            lines.iter().map(|l| l.len()).collect::<Vec<_>>()
+        );
+        assert_eq!(base64::encode(input), lines.concat());
+    }
+
+    #[test]
+    fn test_writer_pem_line_wrap() {
+        let input = [0x5Au8; 96];
+        let mut writer = Base64Writer::with_line_wrap(Vec::new(), LineWrap::PEM);
+        writer.write_all(&input).unwrap();
+        let actual = String::from_utf8(writer.finish().unwrap()).unwrap();
+        let lines = actual.split('\n').collect::<Vec<_>>();
+        assert_eq!(
+            vec![64, 64],
+            lines.iter().map(|l| l.len()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_line_wrap_zero_width() {
+        assert_eq!(None, LineWrap::new(0, b"\n"));
+        let wrap = LineWrap::new(4, b"\n").unwrap();
+        assert_eq!((4, &b"\n"[..]), (wrap.width(), wrap.ending()));
+    }
+
+    /// Writer failing its first few writes
+    struct Flaky {
+        failures: usize,
+        out: Vec<u8>,
+    }
+
+    impl Write for Flaky {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Err(std::io::Error::other("flaky"));
+            }
+            self.out.extend(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_writer_retry_after_error() {
+        let inner = Flaky {
+            failures: 2,
+            out: Vec::new(),
+        };
+        let wrap = LineWrap::new(4, b"\n").unwrap();
+        let mut writer = Base64Writer::with_line_wrap(inner, wrap);
+        writer.write_all(b"Hello").unwrap_err();
+        writer.write_all(b"Hello").unwrap_err();
+        writer.write_all(b"Hello").unwrap();
+        writer.write_all(b" world").unwrap();
+        let actual = writer.finish().unwrap();
+        assert_eq!(&b"SGVs\nbG8g\nd29y\nbGQ="[..], actual.out);
+    }
+
+    #[test]
+    fn test_round_trip_large_input() {
+        let input = pseudo_random_bytes(1_000_000, 0);
+        let mut writer = Base64Writer::with_line_wrap(Vec::new(), LineWrap::MIME);
+        for chunk in input.chunks(1000) {
+            writer.write_all(chunk).unwrap();
+        }
+        let encoded = writer.finish().unwrap();
+        let mut actual = Vec::new();
+        Base64Reader::new(&encoded[..])
+            .read_to_end(&mut actual)
+            .unwrap();
+        assert_eq!(input, actual);
+    }
+
+    #[test]
+    fn test_round_trip_url_safe_no_pad() {
+        let input = [0xfbu8, 0xff, 0xbf, 0x00];
+        let config = Config::URL_SAFE_NO_PAD;
+        let mut writer = Base64Writer::with_options(Vec::new(), config, None);
+        writer.write_all(&input).unwrap();
+        let encoded = writer.finish().unwrap();
+        assert_eq!(&b"-_-_AA"[..], encoded);
+        let mut actual = Vec::new();
+        Base64Reader::with_config(&encoded[..], config)
+            .read_to_end(&mut actual)
+            .unwrap();
+        assert_eq!(&input[..], actual);
+    }
+}
@@ -0,0 +1,251 @@
+use super::CodecError;
+
+/// Z85 alphabet (ZeroMQ RFC 32)
+static Z85_ALPHABET: &[u8; 85] =
+    b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+
+/// Split a 32-bit value into five base85 digits, most significant first
+fn to_digits(n: u32) -> [u8; 5] {
+    let mut digits = [0u8; 5];
+    let mut n = n;
+    for digit in digits.iter_mut().rev() {
+        *digit = (n % 85) as u8;
+        n /= 85;
+    }
+    digits
+}
+
+/// Combine five base85 digits into a 32-bit value
+fn from_digits(digits: &[u8; 5], offset: usize) -> Result<u32, CodecError> {
+    let n = digits.iter().fold(0u64, |acc, &d| acc * 85 + d as u64);
+    u32::try_from(n).map_err(|_| CodecError::ValueOverflow { offset })
+}
+
+/// Encode a byte slice into an Ascii85-encoded string
+///
+/// Groups of four zero bytes are abbreviated as `z`. No `<~`/`~>` delimiters are added.
+pub fn encode<T>(bytes: T) -> String
+where
+    T: AsRef<[u8]>,
+{
+    let bytes = bytes.as_ref();
+    let mut buf = String::with_capacity(bytes.len().div_ceil(4) * 5);
+    for chunk in bytes.chunks(4) {
+        if chunk == [0; 4] {
+            buf.push('z');
+            continue;
+        }
+        // A partial final group is zero-padded and emitted with one digit per input byte plus one
+        let mut group = [0u8; 4];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let digits = to_digits(u32::from_be_bytes(group));
+        buf.extend(
+            digits[..chunk.len() + 1]
+                .iter()
+                .map(|&d| (d + b'!') as char),
+        );
+    }
+    buf
+}
+
+/// Decode an Ascii85-encoded byte slice into raw bytes
+///
+/// Optional `<~`/`~>` delimiters and ASCII whitespace are ignored.
+pub fn decode<T>(s: T) -> Result<Vec<u8>, CodecError>
+where
+    T: AsRef<[u8]>,
+{
+    let s = s.as_ref();
+    // Locate the payload between the optional delimiters
+    let start = s
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(s.len());
+    let end = s
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(start, |i| i + 1);
+    let start = if s[start..end].starts_with(b"<~") {
+        start + 2
+    } else {
+        start
+    };
+    let end = if s[start..end].ends_with(b"~>") {
+        end - 2
+    } else {
+        end
+    };
+    let mut buf = Vec::with_capacity((end - start) / 5 * 4);
+    let mut group = [0u8; 5];
+    let mut len = 0;
+    let mut group_offset = start;
+    let mut symbols = 0usize;
+    for (offset, &byte) in s.iter().enumerate().take(end).skip(start) {
+        if byte.is_ascii_whitespace() {
+            continue;
+        }
+        symbols += 1;
+        match byte {
+            b'z' if len == 0 => buf.extend([0; 4]),
+            b'!'..=b'u' => {
+                if len == 0 {
+                    group_offset = offset;
+                }
+                group[len] = byte - b'!';
+                len += 1;
+                if len == 5 {
+                    buf.extend(from_digits(&group, group_offset)?.to_be_bytes());
+                    len = 0;
+                }
+            }
+            _ => return Err(CodecError::InvalidCharacter { byte, offset }),
+        }
+    }
+    match len {
+        0 => {}
+        1 => return Err(CodecError::TruncatedQuantum { len: symbols }),
+        _ => {
+            // Pad the final group with the highest digit and keep one byte less than its length
+            group[len..].fill(84);
+            let bytes = from_digits(&group, group_offset)?.to_be_bytes();
+            buf.extend(&bytes[..len - 1]);
+        }
+    }
+    Ok(buf)
+}
+
+/// Encode a byte slice into a Z85-encoded string
+///
+/// Z85 requires the input length to be a multiple of four.
+pub fn encode_z85<T>(bytes: T) -> Result<String, CodecError>
+where
+    T: AsRef<[u8]>,
+{
+    let bytes = bytes.as_ref();
+    if !bytes.len().is_multiple_of(4) {
+        return Err(CodecError::TruncatedQuantum { len: bytes.len() });
+    }
+    let mut buf = String::with_capacity(bytes.len() / 4 * 5);
+    for chunk in bytes.chunks(4) {
+        let digits = to_digits(u32::from_be_bytes(chunk.try_into().unwrap()));
+        buf.extend(digits.iter().map(|&d| Z85_ALPHABET[d as usize] as char));
+    }
+    Ok(buf)
+}
+
+/// Decode a Z85-encoded byte slice into raw bytes
+///
+/// ASCII whitespace is ignored.
+pub fn decode_z85<T>(s: T) -> Result<Vec<u8>, CodecError>
+where
+    T: AsRef<[u8]>,
+{
+    let values = s
+        .as_ref()
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| !b.is_ascii_whitespace())
+        .map(
+            |(offset, &byte)| match Z85_ALPHABET.iter().position(|&c| c == byte) {
+                Some(value) => Ok((offset, value as u8)),
+                None => Err(CodecError::InvalidCharacter { byte, offset }),
+            },
+        )
+        .collect::<Result<Vec<_>, _>>()?;
+    if !values.len().is_multiple_of(5) {
+        return Err(CodecError::TruncatedQuantum { len: values.len() });
+    }
+    let mut buf = Vec::with_capacity(values.len() / 5 * 4);
+    for group in values.chunks(5) {
+        let (offset, _) = group[0];
+        let digits = [group[0].1, group[1].1, group[2].1, group[3].1, group[4].1];
+        buf.extend(from_digits(&digits, offset)?.to_be_bytes());
+    }
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod test_codec_base85 {
+    use super::{decode, decode_z85, encode, encode_z85};
+    use crate::codec::CodecError;
+
+    #[test]
+    fn test_encode() {
+        assert_eq!("", encode(b""));
+        assert_eq!("87cURD]j7BEbo7", encode(b"Hello world"));
+        assert_eq!("z!!", encode([0, 0, 0, 0, 0]));
+        assert_eq!("s8W-!", encode([0xff; 4]));
+    }
+
+    #[test]
+    fn test_decode() {
+        let expected = b"Hello world";
+        assert_eq!(expected.to_vec(), decode("87cURD]j7BEbo7").unwrap());
+        assert_eq!(
+            expected.to_vec(),
+            decode(" <~87cUR\nD]j7BEbo7~>\n").unwrap()
+        );
+        assert_eq!(vec![0; 5], decode("z!!").unwrap());
+    }
+
+    #[test]
+    fn test_decode_malformed_input() {
+        let expected = CodecError::InvalidCharacter {
+            byte: b'z',
+            offset: 2,
+        };
+        assert_eq!(Err(expected), decode("87z"));
+        let expected = CodecError::InvalidCharacter {
+            byte: b'v',
+            offset: 1,
+        };
+        assert_eq!(Err(expected), decode("8v"));
+        assert_eq!(
+            Err(CodecError::TruncatedQuantum { len: 6 }),
+            decode("87cURD")
+        );
+        assert_eq!(
+            Err(CodecError::ValueOverflow { offset: 5 }),
+            decode("s8W-!s8W-\"")
+        );
+    }
+
+    #[test]
+    fn test_z85() {
+        // Test vector from ZeroMQ RFC 32
+        let input = [0x86, 0x4F, 0xD2, 0x6F, 0xB5, 0x59, 0xF7, 0x5B];
+        assert_eq!(Ok("HelloWorld".to_owned()), encode_z85(input));
+        assert_eq!(Ok(input.to_vec()), decode_z85("HelloWorld"));
+    }
+
+    #[test]
+    fn test_z85_malformed_input() {
+        assert_eq!(
+            Err(CodecError::TruncatedQuantum { len: 3 }),
+            encode_z85([0; 3])
+        );
+        assert_eq!(
+            Err(CodecError::TruncatedQuantum { len: 4 }),
+            decode_z85("Hell")
+        );
+        let expected = CodecError::InvalidCharacter {
+            byte: b'~',
+            offset: 4,
+        };
+        assert_eq!(Err(expected), decode_z85("Hell~World"));
+        assert_eq!(
+            Err(CodecError::ValueOverflow { offset: 0 }),
+            decode_z85("#####")
+        );
+    }
+
+    #[test]
+    fn test_round_trip_all_lengths() {
+        let input = (0..=u8::MAX).rev().chain([0; 8]).collect::<Vec<_>>();
+        for len in (0..=20).chain(250..=264) {
+            let input = &input[..len];
+            assert_eq!(input, decode(encode(input)).unwrap());
+        }
+        assert_eq!(input, decode_z85(encode_z85(&input).unwrap()).unwrap());
+    }
+}
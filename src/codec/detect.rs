@@ -0,0 +1,160 @@
+use super::{
+    base32,
+    base64::{self, Config, PaddingMode},
+    hex, CodecError, Encoding,
+};
+
+/// Standard base64 with optional padding
+const BASE64_STANDARD: Config = Config::STANDARD.with_padding_mode(PaddingMode::Lenient);
+
+/// URL-safe base64 with optional padding
+const BASE64_URL_SAFE: Config = Config::URL_SAFE.with_padding_mode(PaddingMode::Lenient);
+
+/// Result of classifying the encoding of an input blob
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Detection {
+    /// Detected encoding, or `None` if the input looks like raw binary
+    pub encoding: Option<Encoding>,
+    /// Confidence in the classification, between 0 and 1
+    pub confidence: f32,
+}
+
+/// Classify the input as hex, base64 (standard or URL-safe), base32, or raw binary
+///
+/// Whitespace is ignored. When the input is valid in several encodings, the one with
+/// the smallest alphabet wins, and the confidence reflects how unlikely it is for input
+/// in a larger alphabet to only contain symbols of the detected one by chance.
+pub fn detect_encoding(bytes: &[u8]) -> Detection {
+    let symbols = strip_whitespace(bytes);
+    let data_len = symbols.iter().filter(|&&b| b != b'=').count() as i32;
+    // Probability of a random symbol of the next larger alphabet fitting into the smaller one
+    let confidence = |ratio: f32| 1.0 - ratio.powi(data_len);
+    let detection = |encoding, confidence| Detection {
+        encoding: Some(encoding),
+        confidence,
+    };
+    if symbols.is_empty() {
+        return Detection {
+            encoding: None,
+            confidence: 0.0,
+        };
+    }
+    if hex::decode(&symbols).is_ok() {
+        return detection(Encoding::Hex, confidence(22.0 / 64.0));
+    }
+    if base32::decode(&symbols).is_ok() {
+        return detection(Encoding::Base32, confidence(32.0 / 64.0));
+    }
+    if base64::decode_with(&symbols, &BASE64_STANDARD).is_ok() {
+        return detection(Encoding::Base64(BASE64_STANDARD), confidence(64.0 / 256.0));
+    }
+    if base64::decode_with(&symbols, &BASE64_URL_SAFE).is_ok() {
+        return detection(Encoding::Base64(BASE64_URL_SAFE), confidence(64.0 / 256.0));
+    }
+    // Input that is entirely printable could still be plaintext rather than binary
+    let printable = bytes
+        .iter()
+        .filter(|b| b.is_ascii_graphic() || b.is_ascii_whitespace())
+        .count();
+    Detection {
+        encoding: None,
+        confidence: 1.0 - printable as f32 / bytes.len() as f32 / 2.0,
+    }
+}
+
+/// Decode the input using the encoding found by `detect_encoding`
+///
+/// Raw binary input is returned unchanged.
+pub fn decode_auto(bytes: &[u8]) -> Result<Vec<u8>, CodecError> {
+    match detect_encoding(bytes).encoding {
+        Some(encoding) => encoding.decode(strip_whitespace(bytes)),
+        None => Ok(bytes.to_vec()),
+    }
+}
+
+/// Remove all ASCII whitespace from the input
+fn strip_whitespace(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .iter()
+        .copied()
+        .filter(|b| !b.is_ascii_whitespace())
+        .collect()
+}
+
+#[cfg(test)]
+mod test_codec_detect {
+    use super::{decode_auto, detect_encoding, BASE64_STANDARD, BASE64_URL_SAFE};
+    use crate::codec::{base32, base64, Encoding};
+    use crate::scoring::LetterFrequency;
+    use crate::set1::{challenge6::crack_vigenere_cipher, challenge7::aes_ecb_decrypt};
+
+    #[test]
+    fn test_detect_hex() {
+        for line in include_str!("../data/s1c4.txt").lines() {
+            let actual = detect_encoding(line.as_bytes());
+            assert_eq!(Some(Encoding::Hex), actual.encoding);
+            assert!(actual.confidence > 0.99);
+        }
+        let actual = detect_encoding(include_bytes!("../data/s1c8.txt"));
+        assert_eq!(Some(Encoding::Hex), actual.encoding);
+    }
+
+    #[test]
+    fn test_detect_base64() {
+        let actual = detect_encoding(include_bytes!("../data/s1c6.txt"));
+        assert_eq!(Some(Encoding::Base64(BASE64_STANDARD)), actual.encoding);
+        assert!(actual.confidence > 0.99);
+        let actual = detect_encoding(b"eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9");
+        assert_eq!(Some(Encoding::Base64(BASE64_STANDARD)), actual.encoding);
+        let input = base64::encode_with([0xfb, 0xff, 0x00, 0x01], &base64::Config::URL_SAFE);
+        let actual = detect_encoding(input.as_bytes());
+        assert_eq!(Some(Encoding::Base64(BASE64_URL_SAFE)), actual.encoding);
+    }
+
+    #[test]
+    fn test_detect_base32() {
+        let input = base32::encode(b"Hello world");
+        let actual = detect_encoding(input.as_bytes());
+        assert_eq!(Some(Encoding::Base32), actual.encoding);
+        assert!(actual.confidence > 0.99);
+    }
+
+    #[test]
+    fn test_detect_raw() {
+        let input = (0..=u8::MAX).collect::<Vec<_>>();
+        let actual = detect_encoding(&input);
+        assert_eq!(None, actual.encoding);
+        assert!(actual.confidence > 0.5);
+        let actual = detect_encoding(b"Hello world!");
+        assert_eq!(None, actual.encoding);
+        assert!(actual.confidence <= 0.5);
+        assert_eq!(0.0, detect_encoding(b" \n").confidence);
+    }
+
+    #[test]
+    fn test_short_input_has_low_confidence() {
+        let actual = detect_encoding(b"ab");
+        assert_eq!(Some(Encoding::Hex), actual.encoding);
+        assert!(actual.confidence < 0.9);
+    }
+
+    #[test]
+    fn test_decode_auto_data_files() {
+        let scorer = LetterFrequency::english();
+        let (key, _) = crack_vigenere_cipher(
+            decode_auto(include_bytes!("../data/s1c6.txt")).unwrap(),
+            &scorer,
+        )
+        .unwrap();
+        assert_eq!(&b"Terminator X: Bring the noise"[..], key);
+        let input = decode_auto(include_bytes!("../data/s1c7.txt")).unwrap();
+        let actual = aes_ecb_decrypt(b"YELLOW SUBMARINE", &input).unwrap();
+        assert!(actual.starts_with(b"I'm back and I'm ringin' the bell"));
+    }
+
+    #[test]
+    fn test_decode_auto_raw() {
+        let input = [0x00, 0xff, 0x10];
+        assert_eq!(Ok(input.to_vec()), decode_auto(&input));
+    }
+}
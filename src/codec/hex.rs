@@ -0,0 +1,107 @@
+use super::CodecError;
+
+static HEX_LOOKUP: &[u8] = b"0123456789abcdef";
+
+/// Get the value of the specified ASCII hex digit
+fn decode_digit(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Encode a byte slice into a lowercase hex-encoded string
+pub fn encode<T>(bytes: T) -> String
+where
+    T: AsRef<[u8]>,
+{
+    let bytes = bytes.as_ref();
+    let mut buf = String::with_capacity(bytes.len() * 2);
+    for &b in bytes {
+        buf.push(HEX_LOOKUP[(b >> 4) as usize] as char);
+        buf.push(HEX_LOOKUP[(b & 0x0F) as usize] as char);
+    }
+    buf
+}
+
+/// Decode a hex-encoded byte slice into raw bytes
+pub fn decode<T>(s: T) -> Result<Vec<u8>, CodecError>
+where
+    T: AsRef<[u8]>,
+{
+    let s = s.as_ref();
+    let digit = |offset: usize| {
+        decode_digit(s[offset]).ok_or(CodecError::InvalidCharacter {
+            byte: s[offset],
+            offset,
+        })
+    };
+    let buf = (0..s.len() / 2)
+        .map(|i| Ok(digit(2 * i)? << 4 | digit(2 * i + 1)?))
+        .collect::<Result<Vec<u8>, _>>()?;
+    if s.len() % 2 != 0 {
+        // Report a bad trailing digit before complaining about the length
+        digit(s.len() - 1)?;
+        return Err(CodecError::OddLength { len: s.len() });
+    }
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod test_codec_hex {
+    use super::{decode, encode};
+    use crate::codec::CodecError;
+
+    #[test]
+    fn test_encode() {
+        let input = [0x00, 0x48, 0x7f, 0x80, 0xff];
+        let expected = "00487f80ff";
+        let actual = encode(input);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_decode_mixed_case() {
+        let input = "7F80fFaB";
+        let expected = [0x7f, 0x80, 0xff, 0xab];
+        let actual = decode(input).unwrap();
+        assert_eq!(&expected[..], actual);
+    }
+
+    #[test]
+    fn test_decode_odd_length() {
+        let actual = decode("4869f");
+        assert_eq!(Err(CodecError::OddLength { len: 5 }), actual);
+    }
+
+    #[test]
+    fn test_decode_invalid_character() {
+        let actual = decode("48z9");
+        let expected = CodecError::InvalidCharacter {
+            byte: b'z',
+            offset: 2,
+        };
+        assert_eq!(Err(expected), actual);
+    }
+
+    #[test]
+    fn test_decode_invalid_trailing_character() {
+        let actual = decode("486");
+        assert_eq!(Err(CodecError::OddLength { len: 3 }), actual);
+        let actual = decode("48\n");
+        let expected = CodecError::InvalidCharacter {
+            byte: b'\n',
+            offset: 2,
+        };
+        assert_eq!(Err(expected), actual);
+    }
+
+    #[test]
+    fn test_round_trip_all_bytes() {
+        let input = (0..=u8::MAX).collect::<Vec<_>>();
+        let actual = decode(encode(&input)).unwrap();
+        assert_eq!(input, actual);
+    }
+}
@@ -0,0 +1,128 @@
+use super::CodecError;
+
+/// Maximum number of bytes encoded per line
+const LINE_LEN: usize = 45;
+
+/// Encode a 6-bit value into a uuencoded character, using a backtick for zero
+fn encode_char(value: u8) -> char {
+    match value & 0x3F {
+        0 => '`',
+        value => (value + b' ') as char,
+    }
+}
+
+/// Get the 6-bit value of the specified uuencoded character
+fn decode_char(c: u8, offset: usize) -> Result<u8, CodecError> {
+    match c {
+        b' '..=b'`' => Ok((c - b' ') & 0x3F),
+        byte => Err(CodecError::InvalidCharacter { byte, offset }),
+    }
+}
+
+/// Encode a byte slice into uuencoded lines
+///
+/// The output is terminated by an empty (backtick) line, but does not contain
+/// the `begin`/`end` lines of a complete uuencoded file.
+pub fn encode<T>(bytes: T) -> String
+where
+    T: AsRef<[u8]>,
+{
+    let bytes = bytes.as_ref();
+    let mut buf = String::with_capacity(bytes.len().div_ceil(LINE_LEN) * 62 + 2);
+    for line in bytes.chunks(LINE_LEN) {
+        buf.push(encode_char(line.len() as u8));
+        for chunk in line.chunks(3) {
+            let mut group = [0u8; 3];
+            group[..chunk.len()].copy_from_slice(chunk);
+            let n = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+            buf.extend((0..4).map(|i| encode_char((n >> (18 - 6 * i)) as u8)));
+        }
+        buf.push('\n');
+    }
+    buf.push_str("`\n");
+    buf
+}
+
+/// Decode uuencoded lines into raw bytes
+///
+/// An optional `begin <mode> <name>` header is skipped, and decoding stops at the
+/// first empty (length zero) line or an `end` line.
+pub fn decode<T>(s: T) -> Result<Vec<u8>, CodecError>
+where
+    T: AsRef<[u8]>,
+{
+    let s = s.as_ref();
+    let mut buf = Vec::with_capacity(s.len() / 4 * 3);
+    let mut offset = 0;
+    let mut seen_data = false;
+    for line in s.split(|&b| b == b'\n') {
+        let line_offset = offset;
+        offset += line.len() + 1;
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() || (!seen_data && line.starts_with(b"begin ")) {
+            continue;
+        }
+        if line == b"end" {
+            break;
+        }
+        seen_data = true;
+        let len = decode_char(line[0], line_offset)? as usize;
+        if len == 0 {
+            break;
+        }
+        let chars = &line[1..];
+        let needed = len.div_ceil(3) * 4;
+        if chars.len() < needed {
+            return Err(CodecError::TruncatedQuantum { len: chars.len() });
+        }
+        let mut decoded = Vec::with_capacity(needed / 4 * 3);
+        for (i, group) in chars[..needed].chunks(4).enumerate() {
+            let mut n = 0u32;
+            for (j, &c) in group.iter().enumerate() {
+                n = n << 6 | decode_char(c, line_offset + 1 + 4 * i + j)? as u32;
+            }
+            decoded.extend(&n.to_be_bytes()[1..]);
+        }
+        // Characters past the announced length are only padding
+        buf.extend(&decoded[..len]);
+    }
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod test_codec_uu {
+    use super::{decode, encode};
+    use crate::codec::CodecError;
+
+    #[test]
+    fn test_encode() {
+        assert_eq!("`\n", encode(b""));
+        assert_eq!("#0V%T\n`\n", encode(b"Cat"));
+        assert_eq!("+2&5L;&\\@=V]R;&0`\n`\n", encode(b"Hello world"));
+    }
+
+    #[test]
+    fn test_decode() {
+        let input = "begin 644 hello.txt\r\n+2&5L;&\\@=V]R;&0`\r\n`\r\nend\r\n";
+        assert_eq!(b"Hello world".to_vec(), decode(input).unwrap());
+        assert_eq!(b"Cat".to_vec(), decode("#0V%T").unwrap());
+    }
+
+    #[test]
+    fn test_decode_malformed_input() {
+        let expected = CodecError::InvalidCharacter {
+            byte: b'a',
+            offset: 16,
+        };
+        assert_eq!(Err(expected), decode("begin 644 x\n#0V%a"));
+        assert_eq!(Err(CodecError::TruncatedQuantum { len: 3 }), decode("#0V%"));
+    }
+
+    #[test]
+    fn test_round_trip_multiple_lines() {
+        let input = (0..=u8::MAX).collect::<Vec<_>>();
+        let encoded = encode(&input);
+        assert_eq!(7, encoded.lines().count());
+        assert_eq!(input, decode(encoded).unwrap());
+    }
+}
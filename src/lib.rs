@@ -1,2 +1,11 @@
+pub mod attack;
+pub mod block;
+pub mod codec;
+pub mod scoring;
 pub mod set1;
 pub mod set2;
+pub mod set3;
+pub mod set4;
+#[cfg(test)]
+mod test_util;
+pub mod xor;
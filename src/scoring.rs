@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+
+pub mod model;
+
+/// English letter frequencies, including the space character
+///
+/// Sourced from https://www3.nd.edu/~busiforc/handouts/cryptography/letterfrequencies.html
+pub static ENGLISH_LETTER_FREQUENCIES: [(u8, f32); 27] = [
+    (b' ', 0.182884),
+    (b'e', 0.111607),
+    (b'a', 0.084966),
+    (b'r', 0.075809),
+    (b'i', 0.075448),
+    (b'o', 0.071635),
+    (b't', 0.069509),
+    (b'n', 0.066544),
+    (b's', 0.057351),
+    (b'l', 0.054893),
+    (b'c', 0.045388),
+    (b'u', 0.036308),
+    (b'd', 0.033844),
+    (b'p', 0.031671),
+    (b'm', 0.030129),
+    (b'h', 0.030034),
+    (b'g', 0.024705),
+    (b'b', 0.020720),
+    (b'f', 0.018121),
+    (b'y', 0.017779),
+    (b'w', 0.012899),
+    (b'k', 0.011016),
+    (b'v', 0.010074),
+    (b'x', 0.002902),
+    (b'z', 0.002722),
+    (b'j', 0.001965),
+    (b'q', 0.001962),
+];
+
+/// Most common English letter bigrams, in percent of all bigrams
+///
+/// Sourced from http://norvig.com/mayzner.html
+pub static ENGLISH_BIGRAM_FREQUENCIES: [(&[u8; 2], f32); 40] = [
+    (b"th", 3.56),
+    (b"he", 3.07),
+    (b"in", 2.43),
+    (b"er", 2.05),
+    (b"an", 1.99),
+    (b"re", 1.85),
+    (b"on", 1.76),
+    (b"at", 1.49),
+    (b"en", 1.45),
+    (b"nd", 1.35),
+    (b"ti", 1.34),
+    (b"es", 1.34),
+    (b"or", 1.28),
+    (b"te", 1.20),
+    (b"of", 1.17),
+    (b"ed", 1.17),
+    (b"is", 1.13),
+    (b"it", 1.12),
+    (b"al", 1.09),
+    (b"ar", 1.07),
+    (b"st", 1.05),
+    (b"to", 1.04),
+    (b"nt", 1.04),
+    (b"ng", 0.95),
+    (b"se", 0.93),
+    (b"ha", 0.93),
+    (b"as", 0.87),
+    (b"ou", 0.87),
+    (b"io", 0.83),
+    (b"le", 0.83),
+    (b"ve", 0.83),
+    (b"co", 0.79),
+    (b"me", 0.79),
+    (b"de", 0.76),
+    (b"hi", 0.76),
+    (b"ri", 0.73),
+    (b"ro", 0.73),
+    (b"ic", 0.70),
+    (b"ne", 0.69),
+    (b"ea", 0.69),
+];
+
+/// Scores how likely a candidate plaintext is to be the correct decryption
+///
+/// Higher scores indicate more plausible plaintexts. Scores are only comparable
+/// between results of the same scorer.
+pub trait Scorer {
+    fn score(&self, plaintext: &[u8]) -> f32;
+}
+
+impl<S: Scorer + ?Sized> Scorer for &S {
+    fn score(&self, plaintext: &[u8]) -> f32 {
+        (**self).score(plaintext)
+    }
+}
+
+/// Count occurrences of each case-folded byte in a byte slice
+fn tally(s: &[u8]) -> [f32; 256] {
+    let mut counts = [0f32; 256];
+    for c in s {
+        counts[c.to_ascii_lowercase() as usize] += 1.0;
+    }
+    counts
+}
+
+/// Build a byte-indexed frequency table from `(byte, frequency)` pairs
+fn frequency_table(frequencies: &[(u8, f32)]) -> [f32; 256] {
+    let mut table = [0f32; 256];
+    for &(b, frequency) in frequencies {
+        table[b.to_ascii_lowercase() as usize] = frequency;
+    }
+    table
+}
+
+/// Letter-frequency scorer using the Bhattacharyya coefficient
+#[derive(Debug, Clone)]
+pub struct LetterFrequency {
+    frequencies: [f32; 256],
+}
+
+impl LetterFrequency {
+    /// Create a scorer from `(byte, frequency)` pairs, ignoring case
+    pub fn new(frequencies: &[(u8, f32)]) -> Self {
+        LetterFrequency {
+            frequencies: frequency_table(frequencies),
+        }
+    }
+
+    pub fn english() -> Self {
+        LetterFrequency::new(&ENGLISH_LETTER_FREQUENCIES)
+    }
+}
+
+impl Scorer for LetterFrequency {
+    fn score(&self, plaintext: &[u8]) -> f32 {
+        let tally = tally(plaintext);
+        let total = plaintext.len() as f32;
+        // Bhattacharyya coefficient between the observed and expected distributions
+        self.frequencies
+            .iter()
+            .zip(tally)
+            .map(|(expected, observed)| (observed / total * expected).sqrt())
+            .sum()
+    }
+}
+
+/// Letter-frequency scorer using the negated chi-squared statistic
+///
+/// Bytes missing from the frequency table are pooled into a single bucket.
+#[derive(Debug, Clone)]
+pub struct ChiSquared {
+    frequencies: [f32; 256],
+    /// Expected frequency of bytes missing from the table
+    other: f32,
+}
+
+impl ChiSquared {
+    /// Expected frequency of bytes missing from the table
+    const OTHER_FREQUENCY: f32 = 0.02;
+
+    /// Create a scorer from `(byte, frequency)` pairs, ignoring case
+    pub fn new(frequencies: &[(u8, f32)]) -> Self {
+        let mut frequencies = frequency_table(frequencies);
+        let total = frequencies.iter().sum::<f32>();
+        // Make room for the pooled bucket
+        let scale = (1.0 - ChiSquared::OTHER_FREQUENCY) / total;
+        frequencies.iter_mut().for_each(|f| *f *= scale);
+        ChiSquared {
+            frequencies,
+            other: ChiSquared::OTHER_FREQUENCY,
+        }
+    }
+
+    pub fn english() -> Self {
+        ChiSquared::new(&ENGLISH_LETTER_FREQUENCIES)
+    }
+}
+
+impl Scorer for ChiSquared {
+    fn score(&self, plaintext: &[u8]) -> f32 {
+        let total = plaintext.len() as f32;
+        if plaintext.is_empty() {
+            return f32::NEG_INFINITY;
+        }
+        let tally = tally(plaintext);
+        let mut other = 0.0;
+        let mut chi_squared = 0.0;
+        for (&expected, observed) in self.frequencies.iter().zip(tally) {
+            if expected > 0.0 {
+                let expected = expected * total;
+                chi_squared += (observed - expected).powi(2) / expected;
+            } else {
+                other += observed;
+            }
+        }
+        let expected = self.other * total;
+        chi_squared += (other - expected).powi(2) / expected;
+        // Normalize by length so that scores of differently-sized inputs are comparable
+        -chi_squared / total
+    }
+}
+
+/// Scorer measuring the ratio of printable ASCII characters and common whitespace
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PrintableRatio;
+
+impl Scorer for PrintableRatio {
+    fn score(&self, plaintext: &[u8]) -> f32 {
+        let printable = plaintext
+            .iter()
+            .filter(|&&b| b.is_ascii_graphic() || matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+            .count();
+        printable as f32 / plaintext.len().max(1) as f32
+    }
+}
+
+/// Scorer computing the mean log-likelihood of letter bigrams
+///
+/// Bigrams are formed from adjacent case-folded letters. Unknown bigrams and
+/// unprintable bytes are scored with a fixed floor probability.
+#[derive(Debug, Clone)]
+pub struct BigramLogLikelihood {
+    log_probabilities: HashMap<[u8; 2], f32>,
+    floor: f32,
+}
+
+impl BigramLogLikelihood {
+    /// Probability assigned to unknown bigrams and unprintable bytes
+    const FLOOR_PROBABILITY: f32 = 1e-4;
+
+    /// Create a scorer from `(bigram, frequency)` pairs, ignoring case
+    pub fn new(frequencies: &[(&[u8; 2], f32)]) -> Self {
+        let total = frequencies.iter().map(|(_, f)| f).sum::<f32>();
+        let log_probabilities = frequencies
+            .iter()
+            .map(|(bigram, f)| (bigram.map(|b| b.to_ascii_lowercase()), (f / total).ln()))
+            .collect();
+        BigramLogLikelihood {
+            log_probabilities,
+            floor: BigramLogLikelihood::FLOOR_PROBABILITY.ln(),
+        }
+    }
+
+    pub fn english() -> Self {
+        BigramLogLikelihood::new(&ENGLISH_BIGRAM_FREQUENCIES)
+    }
+}
+
+impl Scorer for BigramLogLikelihood {
+    fn score(&self, plaintext: &[u8]) -> f32 {
+        let bigrams = plaintext
+            .windows(2)
+            .filter(|w| w.iter().all(u8::is_ascii_alphabetic))
+            .map(|w| {
+                let bigram = [w[0].to_ascii_lowercase(), w[1].to_ascii_lowercase()];
+                *self.log_probabilities.get(&bigram).unwrap_or(&self.floor)
+            });
+        let unprintable = plaintext
+            .iter()
+            .filter(|&&b| !(b.is_ascii_graphic() || b.is_ascii_whitespace()))
+            .map(|_| self.floor);
+        let (sum, count) = bigrams
+            .chain(unprintable)
+            .fold((0.0, 0), |(sum, count), x| (sum + x, count + 1));
+        if count == 0 {
+            self.floor
+        } else {
+            sum / count as f32
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_scoring {
+    use super::{BigramLogLikelihood, ChiSquared, LetterFrequency, PrintableRatio, Scorer};
+    use crate::xor::single_byte_xor;
+
+    static ENGLISH: &[u8] = b"Now that the party is jumping with the bass kicked in";
+
+    /// Check that the scorer prefers English text over the same text xored with any other key
+    fn assert_prefers_english(scorer: &dyn Scorer) {
+        let expected = scorer.score(ENGLISH);
+        for key in 1..=u8::MAX {
+            let actual = scorer.score(&single_byte_xor(key, ENGLISH));
+            assert!(expected > actual, "key {}: {} <= {}", key, expected, actual);
+        }
+    }
+
+    #[test]
+    fn test_letter_frequency() {
+        assert_prefers_english(&LetterFrequency::english());
+    }
+
+    #[test]
+    fn test_chi_squared() {
+        assert_prefers_english(&ChiSquared::english());
+        assert_eq!(f32::NEG_INFINITY, ChiSquared::english().score(b""));
+    }
+
+    #[test]
+    fn test_printable_ratio() {
+        assert_eq!(1.0, PrintableRatio.score(ENGLISH));
+        assert_eq!(0.5, PrintableRatio.score(b"a\x00\n\xff"));
+        assert_eq!(0.0, PrintableRatio.score(b""));
+    }
+
+    #[test]
+    fn test_bigram_log_likelihood() {
+        assert_prefers_english(&BigramLogLikelihood::english());
+        let scorer = BigramLogLikelihood::english();
+        assert!(scorer.score(b"the theme") > scorer.score(b"qzx vkjq"));
+    }
+}
@@ -0,0 +1,430 @@
+use std::collections::HashMap;
+use std::{error::Error, fmt, fs, io, path::Path};
+
+use super::Scorer;
+
+/// Magic bytes identifying a serialized frequency model
+const MAGIC: &[u8; 3] = b"FQM";
+
+/// Version of the serialization format
+const VERSION: u8 = 1;
+
+/// Interpolation weights of the trigram, bigram and unigram probabilities
+const WEIGHTS: [f64; 3] = [0.6, 0.3, 0.1];
+
+/// Error produced when deserializing a malformed frequency model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelError {
+    /// The input doesn't start with the model magic bytes
+    InvalidMagic,
+    /// The input was serialized using an unknown format version
+    UnsupportedVersion { version: u8 },
+    /// The input ended in the middle of a value starting at the given offset
+    UnexpectedEof { offset: usize },
+    /// The variable-length integer at the given offset doesn't fit in 64 bits
+    CountOverflow { offset: usize },
+    /// The n-gram at the given offset was already listed
+    DuplicateEntry { offset: usize },
+    /// Unexpected bytes were found after the model at the given offset
+    TrailingData { offset: usize },
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModelError::InvalidMagic => write!(f, "input is not a frequency model"),
+            ModelError::UnsupportedVersion { version } => {
+                write!(f, "unsupported model version {}", version)
+            }
+            ModelError::UnexpectedEof { offset } => {
+                write!(f, "unexpected end of input at offset {}", offset)
+            }
+            ModelError::CountOverflow { offset } => {
+                write!(f, "count at offset {} overflows", offset)
+            }
+            ModelError::DuplicateEntry { offset } => {
+                write!(f, "duplicate n-gram at offset {}", offset)
+            }
+            ModelError::TrailingData { offset } => {
+                write!(f, "trailing data at offset {}", offset)
+            }
+        }
+    }
+}
+
+impl Error for ModelError {}
+
+/// Byte-level unigram, bigram and trigram language model
+///
+/// Models are case-sensitive and include punctuation, whitespace and control
+/// bytes, so they can be trained on any kind of text, such as source code or JSON.
+/// Candidate plaintexts are scored by their mean log-likelihood per byte, using
+/// linear interpolation of the three models with add-one smoothed unigrams.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrequencyModel {
+    unigrams: Vec<u64>,
+    bigrams: HashMap<[u8; 2], u64>,
+    trigrams: HashMap<[u8; 3], u64>,
+    total: u64,
+}
+
+impl FrequencyModel {
+    /// Train a model on a reference corpus
+    pub fn train<T>(corpus: T) -> Self
+    where
+        T: AsRef<[u8]>,
+    {
+        let corpus = corpus.as_ref();
+        let mut unigrams = vec![0; 256];
+        let mut bigrams = HashMap::new();
+        let mut trigrams = HashMap::new();
+        for &b in corpus {
+            unigrams[b as usize] += 1;
+        }
+        for w in corpus.windows(2) {
+            *bigrams.entry([w[0], w[1]]).or_insert(0) += 1;
+        }
+        for w in corpus.windows(3) {
+            *trigrams.entry([w[0], w[1], w[2]]).or_insert(0) += 1;
+        }
+        FrequencyModel {
+            unigrams,
+            bigrams,
+            trigrams,
+            total: corpus.len() as u64,
+        }
+    }
+
+    /// Number of bytes the model was trained on
+    pub fn len(&self) -> u64 {
+        self.total
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    /// Probability of `c` following the bytes `a` and `b`
+    fn probability(&self, a: Option<u8>, b: Option<u8>, c: u8) -> f64 {
+        // Add-one smoothing guarantees that no byte has a probability of zero
+        // Smooth in floating point, as deserialized counts may be as large as `u64::MAX`
+        let unigram = (self.unigrams[c as usize] as f64 + 1.0) / (self.total as f64 + 256.0);
+        // Deserialized tables may be inconsistent, so contexts without any
+        // count leave the estimate to the lower-order terms
+        let bigram = b
+            .map(|b| (self.bigrams.get(&[b, c]), self.unigrams[b as usize]))
+            .and_then(|(count, context)| conditional(*count?, context))
+            .unwrap_or(0.0);
+        let trigram = a
+            .zip(b)
+            .map(|(a, b)| (self.trigrams.get(&[a, b, c]), self.bigrams.get(&[a, b])))
+            .and_then(|(count, context)| conditional(*count?, *context?))
+            .unwrap_or(0.0);
+        WEIGHTS[0] * trigram + WEIGHTS[1] * bigram + WEIGHTS[2] * unigram
+    }
+
+    /// Serialize the model into its compact binary representation
+    ///
+    /// Only n-grams seen during training are stored, as variable-length counts.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        out.push(VERSION);
+        write_count(&mut out, self.total);
+        let unigrams = self
+            .unigrams
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count > 0)
+            .map(|(b, &count)| ([b as u8], count))
+            .collect::<Vec<_>>();
+        write_table(&mut out, unigrams);
+        write_table(
+            &mut out,
+            self.bigrams.iter().map(|(k, &v)| (*k, v)).collect(),
+        );
+        write_table(
+            &mut out,
+            self.trigrams.iter().map(|(k, &v)| (*k, v)).collect(),
+        );
+        out
+    }
+
+    /// Deserialize a model from its compact binary representation
+    pub fn from_bytes<T>(bytes: T) -> Result<Self, ModelError>
+    where
+        T: AsRef<[u8]>,
+    {
+        let mut reader = ByteReader {
+            bytes: bytes.as_ref(),
+            offset: 0,
+        };
+        if reader.take(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+            return Err(ModelError::InvalidMagic);
+        }
+        let version = reader.take(1)?[0];
+        if version != VERSION {
+            return Err(ModelError::UnsupportedVersion { version });
+        }
+        let total = reader.count()?;
+        let mut unigrams = vec![0; 256];
+        for ([b], count) in reader.table::<1>()? {
+            unigrams[b as usize] = count;
+        }
+        let bigrams = reader.table::<2>()?;
+        let trigrams = reader.table::<3>()?;
+        if reader.offset != reader.bytes.len() {
+            return Err(ModelError::TrailingData {
+                offset: reader.offset,
+            });
+        }
+        Ok(FrequencyModel {
+            unigrams,
+            bigrams,
+            trigrams,
+            total,
+        })
+    }
+
+    /// Write the serialized model to a file
+    pub fn save<P>(&self, path: P) -> io::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        fs::write(path, self.to_bytes())
+    }
+
+    /// Read a serialized model from a file
+    pub fn load<P>(path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        FrequencyModel::from_bytes(fs::read(path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl Scorer for FrequencyModel {
+    fn score(&self, plaintext: &[u8]) -> f32 {
+        if plaintext.is_empty() {
+            return f32::NEG_INFINITY;
+        }
+        let log_likelihood = plaintext
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| {
+                let a = i.checked_sub(2).map(|j| plaintext[j]);
+                let b = i.checked_sub(1).map(|j| plaintext[j]);
+                self.probability(a, b, c).ln()
+            })
+            .sum::<f64>();
+        (log_likelihood / plaintext.len() as f64) as f32
+    }
+}
+
+/// Append a LEB128-encoded count
+fn write_count(out: &mut Vec<u8>, mut count: u64) {
+    while count >= 0x80 {
+        out.push(count as u8 | 0x80);
+        count >>= 7;
+    }
+    out.push(count as u8);
+}
+
+/// Probability of an n-gram given the count of its context, if the context was seen
+fn conditional(count: u64, context: u64) -> Option<f64> {
+    (context > 0).then(|| count as f64 / context as f64)
+}
+
+/// Append a table of n-gram counts, sorted so that the output is deterministic
+fn write_table<const N: usize>(out: &mut Vec<u8>, mut entries: Vec<([u8; N], u64)>) {
+    entries.sort_unstable();
+    write_count(out, entries.len() as u64);
+    for (ngram, count) in entries {
+        out.extend_from_slice(&ngram);
+        write_count(out, count);
+    }
+}
+
+/// Cursor over a serialized model
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ModelError> {
+        let slice =
+            self.bytes
+                .get(self.offset..self.offset + len)
+                .ok_or(ModelError::UnexpectedEof {
+                    offset: self.offset,
+                })?;
+        self.offset += len;
+        Ok(slice)
+    }
+
+    /// Read a LEB128-encoded count
+    fn count(&mut self) -> Result<u64, ModelError> {
+        let start = self.offset;
+        let mut count = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self
+                .take(1)
+                .map_err(|_| ModelError::UnexpectedEof { offset: start })?[0];
+            let value = (b & 0x7f) as u64;
+            if value << shift >> shift != value {
+                return Err(ModelError::CountOverflow { offset: start });
+            }
+            count |= value << shift;
+            if b & 0x80 == 0 {
+                return Ok(count);
+            }
+        }
+        Err(ModelError::CountOverflow { offset: start })
+    }
+
+    /// Read a table of n-gram counts
+    fn table<const N: usize>(&mut self) -> Result<HashMap<[u8; N], u64>, ModelError> {
+        let len = self.count()?;
+        let mut table = HashMap::new();
+        for _ in 0..len {
+            let offset = self.offset;
+            let ngram = self.take(N)?.try_into().unwrap();
+            let count = self.count()?;
+            if table.insert(ngram, count).is_some() {
+                return Err(ModelError::DuplicateEntry { offset });
+            }
+        }
+        Ok(table)
+    }
+}
+
+#[cfg(test)]
+mod test_model {
+    use super::{FrequencyModel, ModelError};
+    use crate::scoring::Scorer;
+    use crate::set1::challenge3::crack_single_byte_xor_cipher;
+    use crate::xor::single_byte_xor;
+
+    static CORPUS: &str = r#"[{"id": 1, "name": "alice", "tags": ["admin", "ops"]},
+{"id": 2, "name": "bob", "tags": []}, {"id": 3, "name": "carol", "active": true}]"#;
+
+    #[test]
+    fn test_train() {
+        let model = FrequencyModel::train("abab");
+        assert_eq!(4, model.len());
+        assert_eq!(2, model.unigrams[b'a' as usize]);
+        assert_eq!(Some(&2), model.bigrams.get(b"ab"));
+        assert_eq!(Some(&1), model.bigrams.get(b"ba"));
+        assert_eq!(Some(&1), model.trigrams.get(b"bab"));
+        assert!(FrequencyModel::train("").is_empty());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let model = FrequencyModel::train(CORPUS);
+        let bytes = model.to_bytes();
+        assert_eq!(b"FQM\x01", &bytes[..4]);
+        assert_eq!(Ok(model.clone()), FrequencyModel::from_bytes(&bytes));
+        // Serialization is deterministic
+        assert_eq!(bytes, FrequencyModel::train(CORPUS).to_bytes());
+        let empty = FrequencyModel::train("");
+        assert_eq!(
+            Ok(empty.clone()),
+            FrequencyModel::from_bytes(empty.to_bytes())
+        );
+    }
+
+    #[test]
+    fn test_save_load() {
+        let model = FrequencyModel::train(CORPUS);
+        let path = std::env::temp_dir().join(format!("model-{}.fqm", std::process::id()));
+        model.save(&path).unwrap();
+        let actual = FrequencyModel::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(model, actual.unwrap());
+    }
+
+    #[test]
+    fn test_from_bytes_errors() {
+        let bytes = FrequencyModel::train("abc").to_bytes();
+        assert_eq!(
+            Err(ModelError::InvalidMagic),
+            FrequencyModel::from_bytes(b"FQ")
+        );
+        assert_eq!(
+            Err(ModelError::UnsupportedVersion { version: 2 }),
+            FrequencyModel::from_bytes(b"FQM\x02")
+        );
+        let truncated = &bytes[..bytes.len() - 1];
+        assert_eq!(
+            Err(ModelError::UnexpectedEof {
+                offset: bytes.len() - 1
+            }),
+            FrequencyModel::from_bytes(truncated)
+        );
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            Err(ModelError::TrailingData {
+                offset: bytes.len()
+            }),
+            FrequencyModel::from_bytes(trailing)
+        );
+        assert_eq!(
+            Err(ModelError::CountOverflow { offset: 4 }),
+            FrequencyModel::from_bytes(b"FQM\x01\xff\xff\xff\xff\xff\xff\xff\xff\xff\x7f")
+        );
+        // Two unigram entries for the same byte
+        assert_eq!(
+            Err(ModelError::DuplicateEntry { offset: 8 }),
+            FrequencyModel::from_bytes(b"FQM\x01\x02\x02a\x01a\x01\x00\x00")
+        );
+    }
+
+    #[test]
+    fn test_score() {
+        let model = FrequencyModel::train(CORPUS);
+        assert!(model.score(br#"{"id": 4, "name": "dave"}"#) > model.score(b"Hello, world!"));
+        assert_eq!(f32::NEG_INFINITY, model.score(b""));
+        // An untrained model still assigns every byte a finite score
+        assert!(FrequencyModel::train("").score(b"\x00\xff").is_finite());
+    }
+
+    #[test]
+    fn test_score_huge_counts() {
+        let mut bytes = b"FQM\x01".to_vec();
+        bytes.extend([0xff; 9]);
+        bytes.extend([0x01, 0x00, 0x00, 0x00]);
+        let model = FrequencyModel::from_bytes(&bytes).unwrap();
+        assert_eq!(u64::MAX, model.len());
+        assert!(model.score(b"abc").is_finite());
+        // A single unigram counted `u64::MAX` times
+        let mut bytes = b"FQM\x01\x01\x01a".to_vec();
+        bytes.extend([0xff; 9]);
+        bytes.extend([0x01, 0x00, 0x00]);
+        let model = FrequencyModel::from_bytes(&bytes).unwrap();
+        assert!(model.score(b"abc").is_finite());
+    }
+
+    #[test]
+    fn test_score_inconsistent_tables() {
+        // The bigram "bc" and the trigram "abc" occur, but their contexts never do
+        let model =
+            FrequencyModel::from_bytes(b"FQM\x01\x01\x01c\x01\x01bc\x01\x01abc\x01").unwrap();
+        assert!(model.score(b"abc").is_finite());
+        assert!(model.score(b"bc").is_finite());
+    }
+
+    #[test]
+    fn test_crack_json_payload() {
+        let model = FrequencyModel::train(CORPUS);
+        let expected = br#"{"id":7,"tags":["ops"],"active":true}"#;
+        for key in [0x00, 0x20, 0x42, 0xfe] {
+            let input = single_byte_xor(key, expected);
+            let actual = crack_single_byte_xor_cipher(&input, &model);
+            assert_eq!(key, actual.key);
+            assert_eq!(&expected[..], actual.plaintext);
+        }
+    }
+}
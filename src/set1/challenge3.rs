@@ -1,116 +1,147 @@
-use std::collections::HashMap;
-
-macro_rules! hashmap {
-    ($( $key: expr => $val: expr ),*) => {{
-         let mut map = ::std::collections::HashMap::new();
-         $( map.insert($key, $val); )*
-         map
-    }}
-}
+use crate::scoring::Scorer;
+use crate::xor::single_byte_xor;
 
 #[derive(Debug)]
 pub struct DecodingResult {
     pub key: u8,
     pub score: f32,
-    pub str: String,
+    pub plaintext: Vec<u8>,
 }
 
-/// Crack an xor-encrypted string encrypted using a single-byte key
-pub fn crack_single_byte_xor_cipher<T>(s: T) -> DecodingResult
+/// Decrypt a single-byte-xor-encrypted ciphertext with every possible key,
+/// returning all 256 candidates ranked from most to least plausible
+pub fn rank_single_byte_xor_keys<T, S>(s: T, scorer: &S) -> Vec<DecodingResult>
 where
-    T: AsRef<str>,
+    T: AsRef<[u8]>,
+    S: Scorer + ?Sized,
 {
-    // English letter frequencies
-    // Sourced from https://www3.nd.edu/~busiforc/handouts/cryptography/letterfrequencies.html
-    let english_letter_frequencies: HashMap<char, f32> = hashmap![
-        ' ' => 0.182884,
-        'e' => 0.111607,
-        'a' => 0.084966,
-        'r' => 0.075809,
-        'i' => 0.075448,
-        'o' => 0.071635,
-        't' => 0.069509,
-        'n' => 0.066544,
-        's' => 0.057351,
-        'l' => 0.054893,
-        'c' => 0.045388,
-        'u' => 0.036308,
-        'd' => 0.033844,
-        'p' => 0.031671,
-        'm' => 0.030129,
-        'h' => 0.030034,
-        'g' => 0.024705,
-        'b' => 0.020720,
-        'f' => 0.018121,
-        'y' => 0.017779,
-        'w' => 0.012899,
-        'k' => 0.011016,
-        'v' => 0.010074,
-        'x' => 0.002902,
-        'z' => 0.002722,
-        'j' => 0.001965,
-        'q' => 0.001962
-    ];
-    /// Count occurrences of each character in a string
-    fn tally(s: &str) -> HashMap<char, f32> {
-        let mut result = HashMap::<char, f32>::new();
-        for c in s.chars() {
-            *result.entry(c.to_ascii_lowercase()).or_insert(0.0) += 1.0;
-        }
-        result
-    }
-    // Score the likelihood of the specified string containing valid English text
-    // using the letter-frequency table above and the Bhattacharyya-Distance algorithm
-    let score = |s: &str| -> f32 {
-        let tally = tally(s);
-        let total = s.len() as f32;
-        // Bhattacharyya distance
-        english_letter_frequencies
-            .iter()
-            .map(|(key, value)| {
-                let score = tally.get(key).unwrap_or(&0f32) / total * value;
-                score.sqrt()
-            })
-            .sum()
-    };
-    // Iterate over all possible u8 values
-    (0..u8::MAX)
+    let mut candidates = (0..=u8::MAX)
         // Decrypt all values using the current key-guess
-        .map(|b| {
-            (
-                b,
-                s.as_ref()
-                    .as_bytes()
-                    .iter()
-                    .map(|s| (s ^ b) as char)
-                    .collect::<String>(),
-            )
+        .map(|key| (key, single_byte_xor(key, &s)))
+        // Score each resulting plaintext
+        .map(|(key, plaintext)| DecodingResult {
+            key,
+            score: scorer.score(&plaintext),
+            plaintext,
         })
-        // Score each resulting string according to letter frequencies
-        .map(|(key, s)| (key, score(&s), s))
-        // Find the best candidate based on the previously calculated score
-        .max_by(|(_, score_a, _), (_, score_b, _)| {
-            score_a
-                .partial_cmp(score_b)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        })
-        // Instantiate the decoding result
-        .map(|(key, score, s)| DecodingResult { key, score, str: s })
-        .unwrap()
+        .collect::<Vec<_>>();
+    // Stable sort, so that ties are broken by the lowest key
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    candidates
+}
+
+/// Return the `n` most plausible candidates for a single-byte-xor-encrypted ciphertext
+pub fn top_single_byte_xor_keys<T, S>(s: T, scorer: &S, n: usize) -> Vec<DecodingResult>
+where
+    T: AsRef<[u8]>,
+    S: Scorer + ?Sized,
+{
+    let mut candidates = rank_single_byte_xor_keys(s, scorer);
+    candidates.truncate(n);
+    candidates
+}
+
+/// Score difference between the best and second best of a ranked list of candidates
+///
+/// A small margin indicates an ambiguous crack. Returns `None` if there are
+/// fewer than two candidates.
+pub fn confidence_margin(candidates: &[DecodingResult]) -> Option<f32> {
+    match candidates {
+        [first, second, ..] => Some(first.score - second.score),
+        _ => None,
+    }
+}
+
+/// Crack an xor-encrypted ciphertext encrypted using a single-byte key,
+/// ranking candidate plaintexts with the specified scorer
+pub fn crack_single_byte_xor_cipher<T, S>(s: T, scorer: &S) -> DecodingResult
+where
+    T: AsRef<[u8]>,
+    S: Scorer + ?Sized,
+{
+    // The candidate list always holds one entry per key
+    rank_single_byte_xor_keys(s, scorer).swap_remove(0)
 }
 
 #[cfg(test)]
 mod test_s1_c3 {
-    use super::crack_single_byte_xor_cipher;
+    use super::{
+        confidence_margin, crack_single_byte_xor_cipher, rank_single_byte_xor_keys,
+        top_single_byte_xor_keys,
+    };
+    use crate::scoring::{
+        BigramLogLikelihood, ChiSquared, LetterFrequency, PrintableRatio, Scorer,
+    };
     use crate::set1::challenge1::unhexlify;
+    use crate::xor::single_byte_xor;
+
+    static HEX_INPUT: &str = "1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736";
 
     #[test]
     fn test_crack_single_byte_xor_cipher() {
-        let hex_input = "1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736";
-        let expected = "Cooking MC's like a pound of bacon";
-        let input = unhexlify(hex_input).unwrap();
-        let actual = crack_single_byte_xor_cipher(&input);
+        let expected = b"Cooking MC's like a pound of bacon";
+        let input = unhexlify(HEX_INPUT).unwrap();
+        let actual = crack_single_byte_xor_cipher(&input, &LetterFrequency::english());
         assert_eq!(88_u8, actual.key);
-        assert_eq!(expected, actual.str);
+        assert_eq!(&expected[..], actual.plaintext);
+    }
+
+    #[test]
+    fn test_crack_single_byte_xor_cipher_with_each_scorer() {
+        let input = unhexlify(HEX_INPUT).unwrap();
+        let scorers: [&dyn Scorer; 3] = [
+            &LetterFrequency::english(),
+            &ChiSquared::english(),
+            &BigramLogLikelihood::english(),
+        ];
+        for scorer in scorers {
+            assert_eq!(88_u8, crack_single_byte_xor_cipher(&input, scorer).key);
+        }
+        // Printable characters alone can't tell letters apart from other symbols
+        let actual = crack_single_byte_xor_cipher(&input, &PrintableRatio);
+        assert_eq!(1.0, actual.score);
+    }
+
+    #[test]
+    fn test_crack_single_byte_xor_cipher_key_ff() {
+        let expected = b"Cooking MC's like a pound of bacon";
+        let input = single_byte_xor(0xff, expected);
+        let actual = crack_single_byte_xor_cipher(&input, &LetterFrequency::english());
+        assert_eq!(0xff_u8, actual.key);
+        assert_eq!(&expected[..], actual.plaintext);
+    }
+
+    #[test]
+    fn test_rank_single_byte_xor_keys() {
+        let input = unhexlify(HEX_INPUT).unwrap();
+        let actual = rank_single_byte_xor_keys(&input, &LetterFrequency::english());
+        assert_eq!(256, actual.len());
+        assert_eq!(88_u8, actual[0].key);
+        assert!(actual.windows(2).all(|w| w[0].score >= w[1].score));
+        let mut keys = actual.iter().map(|c| c.key).collect::<Vec<_>>();
+        keys.sort_unstable();
+        assert_eq!((0..=u8::MAX).collect::<Vec<_>>(), keys);
+    }
+
+    #[test]
+    fn test_top_single_byte_xor_keys() {
+        let input = unhexlify(HEX_INPUT).unwrap();
+        let scorer = LetterFrequency::english();
+        let actual = top_single_byte_xor_keys(&input, &scorer, 3);
+        assert_eq!(3, actual.len());
+        assert_eq!(88_u8, actual[0].key);
+        assert_eq!(256, top_single_byte_xor_keys(&input, &scorer, 1000).len());
+        assert!(top_single_byte_xor_keys(&input, &scorer, 0).is_empty());
+    }
+
+    #[test]
+    fn test_confidence_margin() {
+        let input = unhexlify(HEX_INPUT).unwrap();
+        let candidates = rank_single_byte_xor_keys(&input, &LetterFrequency::english());
+        assert!(confidence_margin(&candidates).unwrap() > 0.0);
+        assert_eq!(None, confidence_margin(&candidates[..1]));
+        // Flipping the case of every letter scores the same, so the crack is ambiguous
+        let candidates = rank_single_byte_xor_keys(b"tease", &LetterFrequency::english());
+        assert_eq!(Some(0.0), confidence_margin(&candidates));
     }
 }
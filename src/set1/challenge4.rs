@@ -1,37 +1,117 @@
+use std::{error::Error, fmt};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
 use super::challenge3::{crack_single_byte_xor_cipher, DecodingResult};
+use crate::scoring::Scorer;
+
+/// Error produced when searching for an xor-encrypted ciphertext
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetectionError {
+    /// There are no ciphertexts to search
+    EmptyInput,
+}
+
+impl fmt::Display for DetectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DetectionError::EmptyInput => write!(f, "no ciphertexts to search"),
+        }
+    }
+}
 
-/// Determine which string in a slice of strings is most likely to be xor-encrypted
-pub fn find_xor_encrypted_string<T>(list: &[T]) -> DecodingResult
+impl Error for DetectionError {}
+
+/// Crack every ciphertext in a slice of ciphertexts, returning the index of
+/// each ciphertext with its decoding result, ranked from most to least plausible.
+///
+/// With the `parallel` feature enabled, ciphertexts are cracked across threads.
+pub fn rank_xor_encrypted_strings<T, S>(
+    list: &[T],
+    scorer: &S,
+) -> Result<Vec<(usize, DecodingResult)>, DetectionError>
 where
-    T: AsRef<str>,
+    T: AsRef<[u8]> + Sync,
+    S: Scorer + Sync + ?Sized,
 {
-    list.iter()
+    if list.is_empty() {
+        return Err(DetectionError::EmptyInput);
+    }
+    #[cfg(feature = "parallel")]
+    let iter = list.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let iter = list.iter();
+    let mut results = iter
         // Attempt to decrypt every string
-        .map(|s| crack_single_byte_xor_cipher(s.as_ref()))
-        // Find the result with the best score
-        .max_by(|a, b| {
-            a.score
-                .partial_cmp(&b.score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        })
-        .unwrap()
+        .map(|s| crack_single_byte_xor_cipher(s.as_ref(), scorer))
+        .enumerate()
+        .collect::<Vec<_>>();
+    // Stable sort, so that ties are broken by the lowest index
+    results.sort_by(|(_, a), (_, b)| b.score.total_cmp(&a.score));
+    Ok(results)
+}
+
+/// Determine which ciphertext in a slice of ciphertexts is most likely to be xor-encrypted
+pub fn find_xor_encrypted_string<T, S>(
+    list: &[T],
+    scorer: &S,
+) -> Result<DecodingResult, DetectionError>
+where
+    T: AsRef<[u8]> + Sync,
+    S: Scorer + Sync + ?Sized,
+{
+    // The ranking holds at least one result for non-empty input
+    Ok(rank_xor_encrypted_strings(list, scorer)?.swap_remove(0).1)
 }
 
 #[cfg(test)]
 mod test_s1_c4 {
-    use super::find_xor_encrypted_string;
+    use super::{find_xor_encrypted_string, rank_xor_encrypted_strings, DetectionError};
+    use crate::scoring::LetterFrequency;
     use crate::set1::challenge1::unhexlify;
 
-    #[test]
-    fn test_find_xor_encrypted_string() {
-        let inputs = include_str!("../data/s1c4.txt")
+    fn inputs() -> Vec<Vec<u8>> {
+        include_str!("../data/s1c4.txt")
             .split_ascii_whitespace()
             .map(|s| unhexlify(s).unwrap())
-            .collect::<Vec<_>>();
+            .collect()
+    }
+
+    #[test]
+    fn test_find_xor_encrypted_string() {
+        let inputs = inputs();
         let expected_key = 53_u8;
-        let expected_str = "Now that the party is jumping\n";
-        let actual = find_xor_encrypted_string(&inputs);
+        let expected_str = b"Now that the party is jumping\n";
+        let actual = find_xor_encrypted_string(&inputs, &LetterFrequency::english()).unwrap();
         assert_eq!(expected_key, actual.key);
-        assert_eq!(expected_str, actual.str);
+        assert_eq!(&expected_str[..], actual.plaintext);
+    }
+
+    #[test]
+    fn test_rank_xor_encrypted_strings() {
+        let inputs = inputs();
+        let actual = rank_xor_encrypted_strings(&inputs, &LetterFrequency::english()).unwrap();
+        assert_eq!(inputs.len(), actual.len());
+        assert_eq!(170, actual[0].0);
+        assert_eq!(53_u8, actual[0].1.key);
+        assert!(actual.windows(2).all(|w| w[0].1.score >= w[1].1.score));
+        let mut indices = actual.iter().map(|(i, _)| *i).collect::<Vec<_>>();
+        indices.sort_unstable();
+        assert_eq!((0..inputs.len()).collect::<Vec<_>>(), indices);
+    }
+
+    #[test]
+    fn test_empty_input() {
+        let inputs: [&[u8]; 0] = [];
+        let scorer = LetterFrequency::english();
+        assert_eq!(
+            DetectionError::EmptyInput,
+            find_xor_encrypted_string(&inputs, &scorer).unwrap_err()
+        );
+        assert_eq!(
+            DetectionError::EmptyInput,
+            rank_xor_encrypted_strings(&inputs, &scorer).unwrap_err()
+        );
     }
 }
@@ -1,37 +1,28 @@
-static HEX_LOOKUP: &'static [u8] = b"0123456789abcdef";
+use crate::codec::hex;
+use crate::xor::{self, XorError};
 
-/// Encode a regular string into a hex-encoded string
-pub fn hexlify<T>(s: T) -> String
+/// Encode raw bytes into a hex-encoded string
+pub fn hexlify<T>(bytes: T) -> String
 where
-    T: AsRef<str>,
+    T: AsRef<[u8]>,
 {
-    let mut buf = String::with_capacity(s.as_ref().len() * 2);
-    for b in s.as_ref().bytes() {
-        let chr1 = HEX_LOOKUP[(b >> 4 & 0x0F) as usize] as char;
-        let chr2 = HEX_LOOKUP[(b & 0x0F) as usize] as char;
-        buf.extend([chr1, chr2]);
-    }
-    buf
+    hex::encode(bytes)
 }
 
-/// Xor-encrypt a string using an arbitrarily-sized key
-pub fn repeating_key_xor<K, S>(key: K, s: S) -> String
+/// Xor-encrypt a byte slice using an arbitrarily-sized key
+pub fn repeating_key_xor<K, S>(key: K, s: S) -> Result<Vec<u8>, XorError>
 where
-    K: AsRef<str>,
-    S: AsRef<str>,
+    K: AsRef<[u8]>,
+    S: AsRef<[u8]>,
 {
-    let key_bytes = key.as_ref().as_bytes();
-    let str_bytes = s.as_ref().as_bytes();
-    let mut buf = String::with_capacity(str_bytes.len());
-    for (i, c) in str_bytes.iter().enumerate() {
-        buf.push((c ^ key_bytes[i % key_bytes.len()]) as char);
-    }
-    buf
+    xor::repeating_key_xor(key, s)
 }
 
 #[cfg(test)]
 mod test_s1_c5 {
     use super::{hexlify, repeating_key_xor};
+    use crate::set1::challenge1::unhexlify;
+    use crate::xor::XorError;
 
     #[test]
     fn test_hexlify() {
@@ -45,9 +36,9 @@ mod test_s1_c5 {
     fn test_repeating_key_xor() {
         let input = "Hello world";
         let key = "lmao";
-        let expected = "$\x08\r\x03\x03M\x16\x00\x1e\x01\x05";
-        let actual = repeating_key_xor(key, input);
-        assert_eq!(expected, actual);
+        let expected = b"$\x08\r\x03\x03M\x16\x00\x1e\x01\x05";
+        let actual = repeating_key_xor(key, input).unwrap();
+        assert_eq!(&expected[..], actual);
     }
 
     #[test]
@@ -55,16 +46,31 @@ mod test_s1_c5 {
         let input = "Hello";
         let key = "lmao";
         let expected = "24080d0303";
-        let actual = hexlify(&repeating_key_xor(key, input));
+        let actual = hexlify(repeating_key_xor(key, input).unwrap());
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_repeating_key_xor_high_bytes_round_trip() {
+        let input = "Hello world";
+        let key = [0x80, 0xff, 0xc3];
+        let encrypted = unhexlify(hexlify(repeating_key_xor(key, input).unwrap())).unwrap();
+        assert!(encrypted.iter().all(|&b| b > 0x7f));
+        let actual = repeating_key_xor(key, encrypted).unwrap();
+        assert_eq!(input.as_bytes(), actual);
+    }
+
     #[test]
     fn test_repeating_key_xor_hex_cryptopals() {
         let input = "Burning 'em, if you ain't quick and nimble\nI go crazy when I hear a cymbal";
         let key = "ICE";
         let expected = "0b3637272a2b2e63622c2e69692a23693a2a3c6324202d623d63343c2a26226324272765272a282b2f20430a652e2c652a3124333a653e2b2027630c692b20283165286326302e27282f";
-        let actual = hexlify(&repeating_key_xor(key, input));
+        let actual = hexlify(repeating_key_xor(key, input).unwrap());
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn test_repeating_key_xor_empty_key() {
+        assert_eq!(Err(XorError::EmptyKey), repeating_key_xor("", "Hello"));
+    }
 }
@@ -1,14 +1,12 @@
-use crate::set1::{challenge3::crack_single_byte_xor_cipher, challenge5::repeating_key_xor};
-
-use super::challenge1::BASE64_ENCODE_LUT;
+use crate::codec::{base64, CodecError};
+use crate::scoring::Scorer;
+use crate::set1::challenge3::{confidence_margin, rank_single_byte_xor_keys};
+use crate::xor::keysize::{KeySizeEstimator, KeySizeMethod};
+use crate::xor::{kernel, repeating_key_xor};
 
 /// Calculate the Hamming-Distance between two byte-slices
 pub fn hamming_distance(a: &[u8], b: &[u8]) -> usize {
-    let mut distance: usize = 0;
-    for (byte_a, byte_b) in a.iter().zip(b.iter()) {
-        distance += (byte_a ^ byte_b).count_ones() as usize;
-    }
-    distance
+    kernel::xor_popcount(a, b)
 }
 
 /// Calculate the Hamming-Distance between two strings
@@ -20,100 +18,180 @@ where
     hamming_distance(a.as_ref().as_bytes(), b.as_ref().as_bytes())
 }
 
-/// Decode a base64-encoded string into a regular string
-pub fn base64_decode_bytes(bytes: &[u8]) -> Vec<u8> {
-    let mut buf: Vec<u8> = Vec::new();
-    let pad_len = bytes.iter().rev().take_while(|&&b| b == b'=').count();
-    for b in bytes.chunks(4) {
-        let decode_byte = |b: u8| {
-            BASE64_ENCODE_LUT
-                .iter()
-                .position(|&x| x as u8 == b)
-                .unwrap_or(0)
-        };
-        let n = (decode_byte(b[0]) << 18)
-            + (decode_byte(b[1]) << 12)
-            + (decode_byte(b[2]) << 6)
-            + decode_byte(b[3]);
-        let chars = [(n >> 16) & 0xFF, (n >> 8) & 0xFF, n & 0xFF].map(|x| x as u8);
-        buf.extend(&chars[..]);
+/// Decode a base64-encoded string into raw bytes
+pub fn base64_decode<T>(s: T) -> Result<Vec<u8>, CodecError>
+where
+    T: AsRef<[u8]>,
+{
+    base64::decode(s)
+}
+
+/// Guess the key size for the specified repeating-key-xor-encrypted ciphertext
+///
+/// Returns `None` if the ciphertext is too short to compare two blocks of any key size.
+pub fn find_vigenere_key_size<T>(s: T) -> Option<usize>
+where
+    T: AsRef<[u8]>,
+{
+    // Rank key sizes by their normalized Hamming-Distance over all adjacent blocks
+    KeySizeEstimator::new(KeySizeMethod::HammingDistance)
+        .estimate(s)
+        .first()
+        .map(|score| score.key_size)
+}
+
+/// Single-byte-xor result of one column of a transposed ciphertext
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnReport {
+    pub key: u8,
+    pub score: f32,
+    /// Score difference to the second best key byte, see [`confidence_margin`]
+    pub margin: Option<f32>,
+}
+
+/// Result of cracking a repeating-key-xor-encrypted ciphertext with a single key size
+#[derive(Debug, Clone, PartialEq)]
+pub struct VigenereReport {
+    pub key: Vec<u8>,
+    pub plaintext: Vec<u8>,
+    /// Score of the full plaintext
+    pub score: f32,
+    /// Per-column results, one for each key byte
+    pub columns: Vec<ColumnReport>,
+}
+
+/// Split a ciphertext into columns, so that each column is encrypted with a single key byte
+fn transpose(s: &[u8], key_size: usize) -> Vec<Vec<u8>> {
+    let mut blocks: Vec<Vec<u8>> = (0..key_size)
+        .map(|_| Vec::with_capacity(s.len() / key_size + 1))
+        .collect();
+    for chunk in s.chunks(key_size) {
+        for (block, &b) in blocks.iter_mut().zip(chunk) {
+            block.push(b);
+        }
     }
-    (0..pad_len).for_each(|_| drop(buf.pop().unwrap()));
-    buf
+    blocks
 }
 
-pub fn base64_decode(bytes: &[u8]) -> String {
-    base64_decode_bytes(bytes)
-        .into_iter()
-        .map(|x| x as char)
-        .collect()
+/// Length of the shortest period that the key mostly repeats
+///
+/// Cracking a multiple of the key size leaves fewer bytes per column, so a few
+/// key bytes may come out wrong. A period is accepted if more than three
+/// quarters of the key bytes agree with the most common byte at their position
+/// within the period.
+fn key_period(key: &[u8]) -> usize {
+    (1..key.len())
+        .filter(|&period| key.len().is_multiple_of(period))
+        .find(|&period| {
+            let agreeing = (0..period)
+                .map(|offset| {
+                    let mut counts = [0usize; 256];
+                    for &b in key.iter().skip(offset).step_by(period) {
+                        counts[b as usize] += 1;
+                    }
+                    counts.into_iter().max().unwrap_or(0)
+                })
+                .sum::<usize>();
+            4 * agreeing > 3 * key.len()
+        })
+        .unwrap_or(key.len())
 }
 
-/// Guess the key size for the specified repating-key-xor-encrypted string
-pub fn find_vigenere_key_size<T>(s: T) -> usize
+/// Crack a repeating-key-xor-encrypted ciphertext assuming the specified key size
+///
+/// If the recovered key mostly repeats itself, the ciphertext is cracked again
+/// using the shorter key size, since its columns hold more data.
+fn crack_with_key_size<S>(s: &[u8], key_size: usize, scorer: &S) -> VigenereReport
 where
-    T: AsRef<str>,
+    S: Scorer + ?Sized,
 {
-    (2..=40)
-        .map(|key_size| {
-            // Divide the input string into four chunks of N bytes,
-            // where N is the current guess for key size.
-            let chunks = s
-                .as_ref()
-                .as_bytes()
-                .chunks(key_size)
-                .take(4)
-                .collect::<Vec<_>>();
-            // Calculate a running normalized Hamming-Distance over the chunks
-            let distance = (0..4)
-                .flat_map(|i| {
-                    (i..4)
-                        .map(|j| hamming_distance(chunks[i], chunks[j]) as f32 / key_size as f32)
-                        .collect::<Vec<_>>()
-                })
-                .sum::<f32>();
-            (key_size, distance)
-        })
-        // Find the result with the lowest normalized Hamming-Distance
-        .min_by(|(_, score_a), (_, score_b)| {
-            score_a
-                .partial_cmp(score_b)
-                .unwrap_or(std::cmp::Ordering::Equal)
+    let columns = transpose(s, key_size)
+        .iter()
+        .map(|column| {
+            let candidates = rank_single_byte_xor_keys(column, scorer);
+            ColumnReport {
+                key: candidates[0].key,
+                score: candidates[0].score,
+                margin: confidence_margin(&candidates),
+            }
         })
-        .map(|(key_size, _)| key_size)
-        .unwrap()
+        .collect::<Vec<_>>();
+    let key = columns.iter().map(|column| column.key).collect::<Vec<_>>();
+    let period = key_period(&key);
+    if period < key_size {
+        return crack_with_key_size(s, period, scorer);
+    }
+    // The key is never empty, since key sizes of zero are never estimated
+    let plaintext = repeating_key_xor(&key, s).unwrap();
+    VigenereReport {
+        score: scorer.score(&plaintext),
+        key,
+        plaintext,
+        columns,
+    }
 }
 
-/// Crack a repeating-key-xor-encrypted string by guessing the most probable
-/// key size and computing the most probable key using letter frequencies.
-pub fn crack_vigenere_cipher<T>(s: T) -> (String, String)
+/// Crack a repeating-key-xor-encrypted ciphertext with each of the `top_k` most
+/// probable key sizes, returning the reports ranked by the score of their plaintext.
+///
+/// Key sizes yielding a repetition of a shorter key are reported only once,
+/// using the shorter key.
+pub fn rank_vigenere_keys<T, S>(
+    s: T,
+    scorer: &S,
+    estimator: &KeySizeEstimator,
+    top_k: usize,
+) -> Vec<VigenereReport>
 where
-    T: AsRef<str>,
+    T: AsRef<[u8]>,
+    S: Scorer + ?Sized,
 {
-    let key_size = find_vigenere_key_size(&s);
-    let mut blocks: Vec<String> = (0..key_size)
-        .map(|_| String::with_capacity(key_size))
-        .collect();
-    for chunk in s.as_ref().chars().collect::<Vec<_>>().chunks(key_size) {
-        for i in 0..key_size.min(chunk.len()) {
-            blocks[i].push(chunk[i]);
+    let s = s.as_ref();
+    let mut reports: Vec<VigenereReport> = Vec::new();
+    for estimate in estimator.estimate(s).iter().take(top_k) {
+        let report = crack_with_key_size(s, estimate.key_size, scorer);
+        if reports.iter().all(|r| r.key != report.key) {
+            reports.push(report);
         }
     }
-    let key: String = blocks
-        .iter()
-        .map(|block| crack_single_byte_xor_cipher(block))
-        .map(|result| result.key as char)
-        .collect::<String>();
-    (key.clone(), repeating_key_xor(&key, &s))
+    // Stable sort, so that ties are broken by the most probable key size
+    reports.sort_by(|a, b| b.score.total_cmp(&a.score));
+    reports
+}
+
+/// Number of most probable key sizes tried by [`crack_vigenere_cipher`]
+const CANDIDATE_KEY_SIZES: usize = 5;
+
+/// Crack a repeating-key-xor-encrypted ciphertext by cracking the few most
+/// probable key sizes and keeping the key whose decryption scores best using
+/// the specified scorer.
+///
+/// Returns the recovered key and the decrypted plaintext, or `None` if the
+/// key size can't be guessed.
+pub fn crack_vigenere_cipher<T, S>(s: T, scorer: &S) -> Option<(Vec<u8>, Vec<u8>)>
+where
+    T: AsRef<[u8]>,
+    S: Scorer + ?Sized,
+{
+    let estimator = KeySizeEstimator::new(KeySizeMethod::HammingDistance);
+    rank_vigenere_keys(s, scorer, &estimator, CANDIDATE_KEY_SIZES)
+        .into_iter()
+        .next()
+        .map(|report| (report.key, report.plaintext))
 }
 
 #[cfg(test)]
 mod test_s1_c6 {
-    use crate::set1::challenge6::crack_vigenere_cipher;
+    use super::{
+        base64_decode, crack_vigenere_cipher, find_vigenere_key_size, hamming_distance,
+        hamming_distance_str, key_period, rank_vigenere_keys,
+    };
+    use crate::xor::keysize::{KeySizeEstimator, KeySizeMethod};
+    use crate::xor::repeating_key_xor;
 
-    use super::{base64_decode, find_vigenere_key_size, hamming_distance, hamming_distance_str};
+    use crate::scoring::LetterFrequency;
 
-    static STR: &'static str = include_str!("../data/s1c6.txt");
+    static STR: &str = include_str!("../data/s1c6.txt");
 
     #[test]
     fn test_hamming_distance() {
@@ -134,24 +212,94 @@ mod test_s1_c6 {
     #[test]
     fn test_base64_decode() {
         let input = "SGVsbG8gd29ybGQ=";
-        let expected = "Hello world";
-        let actual = base64_decode(input.as_bytes());
-        assert_eq!(expected, actual);
+        let expected = b"Hello world";
+        let actual = base64_decode(input).unwrap();
+        assert_eq!(&expected[..], actual);
     }
 
     #[test]
     fn test_find_vigenere_key_size() {
-        let expected = 29_usize;
-        let actual = find_vigenere_key_size(base64_decode(STR.as_bytes()));
+        let expected = Some(29_usize);
+        let actual = find_vigenere_key_size(base64_decode(STR).unwrap());
         assert_eq!(expected, actual);
+        assert_eq!(None, find_vigenere_key_size(b"abc"));
     }
 
     #[test]
     fn test_crack_vigenere_cipher() {
-        let expected_key = "Terminator X: Bring the noise";
-        let (actual_key, actual_result) = crack_vigenere_cipher(base64_decode(STR.as_bytes()));
-        assert_eq!(expected_key, actual_key);
-        assert!(actual_result.starts_with("I'm back and I'm ringin' the bell"));
-        assert!(actual_result.ends_with("Play that funky music \n"));
+        let expected_key = b"Terminator X: Bring the noise";
+        let (actual_key, actual_result) =
+            crack_vigenere_cipher(base64_decode(STR).unwrap(), &LetterFrequency::english())
+                .unwrap();
+        assert_eq!(&expected_key[..], actual_key);
+        assert!(actual_result.starts_with(b"I'm back and I'm ringin' the bell"));
+        assert!(actual_result.ends_with(b"Play that funky music \n"));
+    }
+
+    #[test]
+    fn test_key_period() {
+        assert_eq!(3, key_period(b"abcabcabc"));
+        assert_eq!(1, key_period(b"aaaa"));
+        assert_eq!(4, key_period(b"abca"));
+        assert_eq!(0, key_period(b""));
+        // Keys that mostly repeat have the shorter period
+        assert_eq!(3, key_period(b"ICEICEICEI_EICEICEICE"));
+        assert_eq!(4, key_period(b"abac"));
+    }
+
+    #[test]
+    fn test_rank_vigenere_keys() {
+        let expected_key = b"Terminator X: Bring the noise";
+        let input = base64_decode(STR).unwrap();
+        let estimator = KeySizeEstimator::new(KeySizeMethod::HammingDistance);
+        let actual = rank_vigenere_keys(&input, &LetterFrequency::english(), &estimator, 5);
+        assert_eq!(5, actual.len());
+        assert_eq!(&expected_key[..], actual[0].key);
+        assert!(actual[0]
+            .plaintext
+            .starts_with(b"I'm back and I'm ringin' the bell"));
+        assert!(actual.windows(2).all(|w| w[0].score >= w[1].score));
+        assert_eq!(29, actual[0].columns.len());
+        assert!(actual[0].columns.iter().all(|c| c.margin.unwrap() > 0.0));
+    }
+
+    #[test]
+    fn test_rank_vigenere_keys_repeated_key() {
+        let expected_key = b"Terminator X: Bring the noise";
+        let input = base64_decode(STR).unwrap();
+        // Only multiples of the key size are estimated
+        let estimator =
+            KeySizeEstimator::new(KeySizeMethod::IndexOfCoincidence).with_range(58..=58);
+        let actual = rank_vigenere_keys(&input, &LetterFrequency::english(), &estimator, 1);
+        assert_eq!(1, actual.len());
+        assert_eq!(&expected_key[..], actual[0].key);
+        assert_eq!(29, actual[0].columns.len());
+        // Both key sizes yield the same key, which is only reported once
+        let estimator = estimator.with_range(29..=58);
+        let actual = rank_vigenere_keys(&input, &LetterFrequency::english(), &estimator, 2);
+        assert_eq!(1, actual.len());
+    }
+
+    #[test]
+    fn test_crack_vigenere_cipher_top_key_sizes() {
+        let key = b"abcdefg";
+        let plaintext =
+            repeating_key_xor("Terminator X: Bring the noise", base64_decode(STR).unwrap())
+                .unwrap();
+        // The most probable key size isn't a multiple of the key size, but one
+        // of the next few most probable key sizes is
+        let input = repeating_key_xor(key, &plaintext[..240]).unwrap();
+        let (actual_key, actual_result) =
+            crack_vigenere_cipher(&input, &LetterFrequency::english()).unwrap();
+        assert_eq!(&key[..], actual_key);
+        assert_eq!(&plaintext[..240], actual_result);
+    }
+
+    #[test]
+    fn test_crack_vigenere_cipher_short_input() {
+        assert_eq!(
+            None,
+            crack_vigenere_cipher(b"abc", &LetterFrequency::english())
+        );
     }
 }
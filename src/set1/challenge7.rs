@@ -1,30 +1,44 @@
-use aes::{
-    cipher::{consts::U16, generic_array::GenericArray, BlockDecrypt, KeyInit},
-    Aes128,
-};
+use crate::block::{ecb::Ecb, BlockError, BlockMode};
 
-pub fn aes_ecb_decrypt(key: &[u8], s: &[u8]) -> String {
-    let key: GenericArray<_, U16> = GenericArray::clone_from_slice(key);
-    let mut blocks: Vec<GenericArray<_, U16>> = s
-        .chunks(16)
-        .map(|block| GenericArray::clone_from_slice(block))
-        .collect::<Vec<_>>();
-    Aes128::new(&key).decrypt_blocks(&mut blocks);
-    blocks.iter().flatten().map(|&x| x as char).collect()
+/// Decrypt an AES-ECB-encrypted ciphertext and strip its PKCS#7 padding
+pub fn aes_ecb_decrypt(key: &[u8], s: &[u8]) -> Result<Vec<u8>, BlockError> {
+    Ecb::new(key)?.decrypt(s)
+}
+
+/// Decrypt an AES-ECB-encrypted ciphertext, keeping any padding
+pub fn aes_ecb_decrypt_blocks(key: &[u8], s: &[u8]) -> Result<Vec<u8>, BlockError> {
+    Ecb::new(key)?.decrypt_blocks(s)
+}
+
+/// Encrypt a plaintext using AES-ECB, padding it using PKCS#7
+pub fn aes_ecb_encrypt(key: &[u8], s: &[u8]) -> Result<Vec<u8>, BlockError> {
+    Ok(Ecb::new(key)?.encrypt(s))
 }
 
 #[cfg(test)]
 mod test_s1_c7 {
-    use crate::set1::challenge6::base64_decode_bytes;
+    use crate::set1::challenge6::base64_decode;
 
-    use super::aes_ecb_decrypt;
+    use super::{aes_ecb_decrypt, aes_ecb_decrypt_blocks, aes_ecb_encrypt};
 
     #[test]
     fn test_aes_ecb_decrypt() {
-        let input = base64_decode_bytes(include_bytes!("../data/s1c7.txt"));
+        let input = base64_decode(include_bytes!("../data/s1c7.txt")).unwrap();
+        let key = b"YELLOW SUBMARINE";
+        let actual = aes_ecb_decrypt_blocks(key, &input).unwrap();
+        assert!(actual.starts_with(b"I'm back and I'm ringin' the bell"));
+        assert!(actual.ends_with(b"Play that funky music \n\x04\x04\x04\x04"));
+        let actual = aes_ecb_decrypt(key, &input).unwrap();
+        assert!(actual.ends_with(b"Play that funky music \n"));
+    }
+
+    #[test]
+    fn test_aes_ecb_encrypt() {
+        let input = base64_decode(include_bytes!("../data/s1c7.txt")).unwrap();
         let key = b"YELLOW SUBMARINE";
-        let actual = aes_ecb_decrypt(key, &input);
-        assert!(actual.starts_with("I'm back and I'm ringin' the bell"));
-        assert!(actual.ends_with("Play that funky music \n\u{4}\u{4}\u{4}\u{4}"));
+        let plaintext = aes_ecb_decrypt(key, &input).unwrap();
+        let actual = aes_ecb_encrypt(key, &plaintext).unwrap();
+        assert_eq!(input, actual);
+        assert_eq!(plaintext, aes_ecb_decrypt(key, &actual).unwrap());
     }
 }
@@ -1 +1,8 @@
+pub mod challenge10;
+pub mod challenge11;
+pub mod challenge12;
+pub mod challenge13;
+pub mod challenge14;
+pub mod challenge15;
+pub mod challenge16;
 pub mod challenge9;
@@ -0,0 +1,34 @@
+use crate::block::{cbc::Cbc, BlockError, BlockMode};
+
+/// Encrypt a plaintext using AES-CBC, padding it using PKCS#7
+pub fn aes_cbc_encrypt(key: &[u8], iv: &[u8], s: &[u8]) -> Result<Vec<u8>, BlockError> {
+    Ok(Cbc::new(key, iv)?.encrypt(s))
+}
+
+/// Decrypt an AES-CBC-encrypted ciphertext and strip its PKCS#7 padding
+pub fn aes_cbc_decrypt(key: &[u8], iv: &[u8], s: &[u8]) -> Result<Vec<u8>, BlockError> {
+    Cbc::new(key, iv)?.decrypt(s)
+}
+
+#[cfg(test)]
+mod test_s2_c10 {
+    use super::{aes_cbc_decrypt, aes_cbc_encrypt};
+    use crate::codec::base64;
+
+    static KEY: &[u8; 16] = b"YELLOW SUBMARINE";
+
+    #[test]
+    fn test_aes_cbc_decrypt() {
+        let input = base64::decode(include_bytes!("../data/s2c10.txt")).unwrap();
+        let actual = aes_cbc_decrypt(KEY, &[0; 16], &input).unwrap();
+        assert!(actual.starts_with(b"I'm back and I'm ringin' the bell"));
+        assert!(actual.ends_with(b"Play that funky music \n"));
+    }
+
+    #[test]
+    fn test_aes_cbc_encrypt() {
+        let input = base64::decode(include_bytes!("../data/s2c10.txt")).unwrap();
+        let plaintext = aes_cbc_decrypt(KEY, &[0; 16], &input).unwrap();
+        assert_eq!(input, aes_cbc_encrypt(KEY, &[0; 16], &plaintext).unwrap());
+    }
+}
@@ -0,0 +1,118 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::block::{
+    cbc::Cbc,
+    detect::{detect_oracle_mode, CipherMode},
+    ecb::Ecb,
+    BlockMode, BLOCK_SIZE,
+};
+
+/// Generate a random AES-128 key
+pub fn random_aes_key<R>(rng: &mut R) -> [u8; 16]
+where
+    R: Rng + ?Sized,
+{
+    rng.gen()
+}
+
+/// Encrypt the input under a random key using either ECB or CBC with a random IV,
+/// chosen at random, after surrounding it with 5 to 10 random bytes on each side.
+///
+/// Returns the chosen mode along with the ciphertext.
+pub fn encryption_oracle<R>(rng: &mut R, input: &[u8]) -> (CipherMode, Vec<u8>)
+where
+    R: Rng + ?Sized,
+{
+    let key = random_aes_key(rng);
+    let mut plaintext = Vec::with_capacity(input.len() + 20);
+    let prefix_len = rng.gen_range(5..=10);
+    plaintext.extend((0..prefix_len).map(|_| rng.gen::<u8>()));
+    plaintext.extend(input);
+    let suffix_len = rng.gen_range(5..=10);
+    plaintext.extend((0..suffix_len).map(|_| rng.gen::<u8>()));
+    // The key and IV are always valid, since both are one block long
+    if rng.gen() {
+        let ecb = Ecb::new(&key).unwrap();
+        (CipherMode::Ecb, ecb.encrypt(&plaintext))
+    } else {
+        let iv = rng.gen::<[u8; BLOCK_SIZE]>();
+        let cbc = Cbc::new(&key, &iv).unwrap();
+        (CipherMode::Cbc, cbc.encrypt(&plaintext))
+    }
+}
+
+/// Outcome of repeatedly detecting the mode of the encryption oracle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DetectionReport {
+    pub trials: usize,
+    pub correct: usize,
+    /// Number of trials in which the oracle used ECB
+    pub ecb: usize,
+}
+
+impl DetectionReport {
+    /// Ratio of correctly detected modes to all trials
+    pub fn accuracy(&self) -> f32 {
+        self.correct as f32 / self.trials.max(1) as f32
+    }
+}
+
+/// Run the mode detector against the encryption oracle for a number of trials,
+/// seeding the oracle so that runs are reproducible
+pub fn run_detection_trials(seed: u64, trials: usize) -> DetectionReport {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut report = DetectionReport::default();
+    for _ in 0..trials {
+        let mut actual = None;
+        let detected = detect_oracle_mode(
+            |input| {
+                let (mode, ciphertext) = encryption_oracle(&mut rng, input);
+                actual = Some(mode);
+                ciphertext
+            },
+            BLOCK_SIZE,
+        );
+        let actual = actual.unwrap();
+        report.trials += 1;
+        report.correct += (detected == actual) as usize;
+        report.ecb += (actual == CipherMode::Ecb) as usize;
+    }
+    report
+}
+
+#[cfg(test)]
+mod test_s2_c11 {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{encryption_oracle, run_detection_trials};
+
+    #[test]
+    fn test_encryption_oracle_is_reproducible() {
+        let input = b"Reproducible with the same seed";
+        let a = encryption_oracle(&mut StdRng::seed_from_u64(11), input);
+        let b = encryption_oracle(&mut StdRng::seed_from_u64(11), input);
+        assert_eq!(a, b);
+        let c = encryption_oracle(&mut StdRng::seed_from_u64(12), input);
+        assert_ne!(a.1, c.1);
+    }
+
+    #[test]
+    fn test_encryption_oracle_length() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..100 {
+            let (_, ciphertext) = encryption_oracle(&mut rng, &[0; 16]);
+            // 16 input bytes, 10 to 20 random bytes and 1 to 16 bytes of padding
+            assert!(ciphertext.len() == 32 || ciphertext.len() == 48);
+        }
+    }
+
+    #[test]
+    fn test_run_detection_trials() {
+        let report = run_detection_trials(1337, 1000);
+        assert_eq!(1000, report.trials);
+        assert_eq!(1.0, report.accuracy());
+        // Both modes are picked a reasonable number of times
+        assert!((400..=600).contains(&report.ecb), "{:?}", report);
+        assert_eq!(report, run_detection_trials(1337, 1000));
+    }
+}
@@ -0,0 +1,67 @@
+use rand::Rng;
+
+use crate::block::{ecb::Ecb, BlockMode};
+use crate::set2::challenge11::random_aes_key;
+
+/// Base64-encoded secret appended to every input by the challenge oracle
+pub static SECRET: &str = "Um9sbGluJyBpbiBteSA1LjAKV2l0aCBteSByYWctdG9wIGRvd24gc28gbXkgaGFpciBjYW4gYmxvdwpUaGUgZ2lybGllcyBvbiBzdGFuZGJ5IHdhdmluZyBqdXN0IHRvIHNheSBoaQpEaWQgeW91IHN0b3A/IE5vLCBJIGp1c3QgZHJvdmUgYnkK";
+
+/// Oracle encrypting `prefix || input || secret` using AES-ECB under a fixed random key
+pub struct EcbOracle {
+    ecb: Ecb,
+    prefix: Vec<u8>,
+    secret: Vec<u8>,
+}
+
+impl EcbOracle {
+    /// Create an oracle appending the secret to every input
+    pub fn new<R>(rng: &mut R, secret: &[u8]) -> Self
+    where
+        R: Rng + ?Sized,
+    {
+        EcbOracle::with_prefix(rng, &[], secret)
+    }
+
+    /// Create an oracle also prepending a fixed prefix to every input
+    pub fn with_prefix<R>(rng: &mut R, prefix: &[u8], secret: &[u8]) -> Self
+    where
+        R: Rng + ?Sized,
+    {
+        EcbOracle {
+            // A random AES-128 key is always valid
+            ecb: Ecb::new(&random_aes_key(rng)).unwrap(),
+            prefix: prefix.to_vec(),
+            secret: secret.to_vec(),
+        }
+    }
+
+    pub fn encrypt(&self, input: &[u8]) -> Vec<u8> {
+        let mut plaintext = Vec::with_capacity(self.prefix.len() + input.len() + self.secret.len());
+        plaintext.extend(&self.prefix);
+        plaintext.extend(input);
+        plaintext.extend(&self.secret);
+        self.ecb.encrypt(&plaintext)
+    }
+}
+
+#[cfg(test)]
+mod test_s2_c12 {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{EcbOracle, SECRET};
+    use crate::attack::byte_at_a_time::ByteAtATime;
+    use crate::codec::base64;
+
+    #[test]
+    fn test_byte_at_a_time_ecb_decryption() {
+        let secret = base64::decode(SECRET).unwrap();
+        let oracle = EcbOracle::new(&mut StdRng::seed_from_u64(12), &secret);
+        let actual = ByteAtATime::new(|input: &[u8]| oracle.encrypt(input))
+            .recover()
+            .unwrap();
+        assert_eq!(16, actual.block_size);
+        assert_eq!(0, actual.prefix_len);
+        assert!(actual.secret.starts_with(b"Rollin' in my 5.0\n"));
+        assert_eq!(secret, actual.secret);
+    }
+}
@@ -0,0 +1,245 @@
+use std::{error::Error, fmt};
+
+use crate::block::{ecb::Ecb, BlockError, BlockMode, BLOCK_SIZE};
+
+/// Error produced when parsing a `key=value&...` cookie
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CookieError {
+    /// The pair at the given offset doesn't contain a `=`
+    MissingSeparator { offset: usize },
+    /// The percent escape at the given offset isn't followed by two hex digits
+    InvalidEscape { offset: usize },
+    /// The unescaped text isn't valid UTF-8
+    InvalidUtf8,
+    /// The ciphertext couldn't be decrypted
+    Decryption(BlockError),
+}
+
+impl fmt::Display for CookieError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CookieError::MissingSeparator { offset } => {
+                write!(f, "missing '=' in pair at offset {}", offset)
+            }
+            CookieError::InvalidEscape { offset } => {
+                write!(f, "invalid escape at offset {}", offset)
+            }
+            CookieError::InvalidUtf8 => write!(f, "invalid UTF-8"),
+            CookieError::Decryption(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for CookieError {}
+
+impl From<BlockError> for CookieError {
+    fn from(e: BlockError) -> Self {
+        CookieError::Decryption(e)
+    }
+}
+
+/// Percent-escape the metacharacters `&`, `=` and `%`
+pub fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("%26"),
+            '=' => out.push_str("%3D"),
+            '%' => out.push_str("%25"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Resolve percent escapes, reporting offsets relative to the start of the cookie
+fn unescape(s: &str, offset: usize) -> Result<String, CookieError> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let value = bytes
+                .get(i + 1..i + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or(CookieError::InvalidEscape { offset: offset + i })?;
+            out.push(value);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).map_err(|_| CookieError::InvalidUtf8)
+}
+
+/// Parse a `key=value&...` cookie into its pairs, in order
+pub fn parse_cookie(s: &str) -> Result<Vec<(String, String)>, CookieError> {
+    let mut offset = 0;
+    let mut pairs = Vec::new();
+    for pair in s.split('&') {
+        let (key, value) = pair
+            .split_once('=')
+            .ok_or(CookieError::MissingSeparator { offset })?;
+        pairs.push((
+            unescape(key, offset)?,
+            unescape(value, offset + key.len() + 1)?,
+        ));
+        offset += pair.len() + 1;
+    }
+    Ok(pairs)
+}
+
+/// Encode the pairs of a cookie, escaping all metacharacters
+pub fn encode_cookie(pairs: &[(&str, &str)]) -> String {
+    pairs
+        .iter()
+        .map(|(key, value)| format!("{}={}", escape(key), escape(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Encode the user profile cookie for an email address
+pub fn profile_for(email: &str) -> String {
+    encode_cookie(&[("email", email), ("uid", "10"), ("role", "user")])
+}
+
+/// Service handing out and checking AES-ECB-encrypted profile cookies
+pub struct ProfileService {
+    ecb: Ecb,
+}
+
+impl ProfileService {
+    pub fn new(key: &[u8]) -> Result<Self, BlockError> {
+        Ok(ProfileService {
+            ecb: Ecb::new(key)?,
+        })
+    }
+
+    /// Encrypt the profile cookie for an email address
+    pub fn encrypt_profile(&self, email: &str) -> Vec<u8> {
+        self.ecb.encrypt(profile_for(email).as_bytes())
+    }
+
+    /// Decrypt and parse a profile cookie
+    pub fn decrypt_profile(&self, ciphertext: &[u8]) -> Result<Vec<(String, String)>, CookieError> {
+        let plaintext = self.ecb.decrypt(ciphertext)?;
+        let cookie = String::from_utf8(plaintext).map_err(|_| CookieError::InvalidUtf8)?;
+        parse_cookie(&cookie)
+    }
+
+    /// Check whether a profile cookie grants the admin role
+    pub fn is_admin(&self, ciphertext: &[u8]) -> bool {
+        self.decrypt_profile(ciphertext)
+            .map(|pairs| pairs.iter().any(|(k, v)| k == "role" && v == "admin"))
+            .unwrap_or(false)
+    }
+}
+
+/// Forge an encrypted `role=admin` profile cookie by cutting and pasting
+/// ciphertext blocks produced by a profile encryption oracle
+///
+/// The oracle is only fed escaping-safe email addresses. One of them places a
+/// padded `admin` block on a block boundary, the other one ends the ciphertext
+/// right after `role=`, so that the two can be spliced together.
+pub fn forge_admin_profile<F>(oracle: F) -> Vec<u8>
+where
+    F: Fn(&str) -> Vec<u8>,
+{
+    let prefix_len = "email=".len();
+    let fill_len = BLOCK_SIZE - prefix_len % BLOCK_SIZE;
+    // Padding bytes below 0x10 are no metacharacters and survive escaping
+    let pad_len = BLOCK_SIZE - "admin".len();
+    let mut email = "A".repeat(fill_len) + "admin";
+    email.extend(std::iter::repeat_n(pad_len as u8 as char, pad_len));
+    let admin_offset = prefix_len + fill_len;
+    let admin_ciphertext = oracle(&email);
+    let admin_block = &admin_ciphertext[admin_offset..admin_offset + BLOCK_SIZE];
+    // Push `user` into its own block
+    let fixed_len = prefix_len + "&uid=10&role=".len();
+    let email_len = BLOCK_SIZE - fixed_len % BLOCK_SIZE;
+    let email = "a".repeat(email_len - "@x.io".len()) + "@x.io";
+    let mut forged = oracle(&email);
+    forged.truncate(fixed_len + email_len);
+    forged.extend(admin_block);
+    forged
+}
+
+#[cfg(test)]
+mod test_s2_c13 {
+    use super::{
+        encode_cookie, forge_admin_profile, parse_cookie, profile_for, CookieError, ProfileService,
+    };
+
+    fn pairs(s: &str) -> Vec<(String, String)> {
+        parse_cookie(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse_cookie() {
+        let expected = vec![
+            ("foo".to_string(), "bar".to_string()),
+            ("baz".to_string(), "qux".to_string()),
+            ("zap".to_string(), "zazzle".to_string()),
+        ];
+        assert_eq!(expected, pairs("foo=bar&baz=qux&zap=zazzle"));
+        assert_eq!(vec![("a".to_string(), "b=c".to_string())], pairs("a=b=c"));
+    }
+
+    #[test]
+    fn test_parse_cookie_errors() {
+        assert_eq!(
+            Err(CookieError::MissingSeparator { offset: 4 }),
+            parse_cookie("a=b&c&d=e")
+        );
+        assert_eq!(
+            Err(CookieError::InvalidEscape { offset: 6 }),
+            parse_cookie("a=b&c=%2")
+        );
+        assert_eq!(
+            Err(CookieError::InvalidEscape { offset: 2 }),
+            parse_cookie("a=%zz")
+        );
+    }
+
+    #[test]
+    fn test_profile_for() {
+        assert_eq!(
+            "email=foo@bar.com&uid=10&role=user",
+            profile_for("foo@bar.com")
+        );
+    }
+
+    #[test]
+    fn test_escaping_stops_injection() {
+        let cookie = profile_for("foo@bar.com&role=admin");
+        assert_eq!("email=foo@bar.com%26role%3Dadmin&uid=10&role=user", cookie);
+        let actual = pairs(&cookie);
+        assert_eq!(3, actual.len());
+        assert_eq!("foo@bar.com&role=admin", actual[0].1);
+        assert_eq!(("role".to_string(), "user".to_string()), actual[2]);
+        // Escaping round-trips arbitrary metacharacters
+        let value = "100% & = %26";
+        assert_eq!(value, pairs(&encode_cookie(&[("k", value)]))[0].1);
+    }
+
+    #[test]
+    fn test_encrypted_injection_fails() {
+        let service = ProfileService::new(b"YELLOW SUBMARINE").unwrap();
+        let ciphertext = service.encrypt_profile("foo@bar.com&role=admin");
+        assert!(!service.is_admin(&ciphertext));
+        let actual = service.decrypt_profile(&ciphertext).unwrap();
+        assert_eq!("foo@bar.com&role=admin", actual[0].1);
+    }
+
+    #[test]
+    fn test_forge_admin_profile() {
+        let service = ProfileService::new(b"YELLOW SUBMARINE").unwrap();
+        let forged = forge_admin_profile(|email| service.encrypt_profile(email));
+        assert!(service.is_admin(&forged));
+        let actual = service.decrypt_profile(&forged).unwrap();
+        assert_eq!(("role".to_string(), "admin".to_string()), actual[2]);
+        assert_eq!("aaaaaaaa@x.io", actual[0].1);
+    }
+}
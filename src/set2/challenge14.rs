@@ -0,0 +1,35 @@
+use rand::Rng;
+
+use crate::set2::challenge12::EcbOracle;
+
+/// Create an oracle prepending a random count of random bytes to every input
+pub fn random_prefix_oracle<R>(rng: &mut R, secret: &[u8]) -> EcbOracle
+where
+    R: Rng + ?Sized,
+{
+    let prefix_len = rng.gen_range(0..=64);
+    let prefix = (0..prefix_len).map(|_| rng.gen()).collect::<Vec<u8>>();
+    EcbOracle::with_prefix(rng, &prefix, secret)
+}
+
+#[cfg(test)]
+mod test_s2_c14 {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::random_prefix_oracle;
+    use crate::attack::byte_at_a_time::ByteAtATime;
+    use crate::codec::base64;
+    use crate::set2::challenge12::SECRET;
+
+    #[test]
+    fn test_byte_at_a_time_ecb_decryption_with_prefix() {
+        let secret = base64::decode(SECRET).unwrap();
+        for seed in 0..20 {
+            let oracle = random_prefix_oracle(&mut StdRng::seed_from_u64(seed), &secret);
+            let actual = ByteAtATime::new(|input: &[u8]| oracle.encrypt(input))
+                .recover()
+                .unwrap();
+            assert_eq!(secret, actual.secret, "seed {}", seed);
+        }
+    }
+}
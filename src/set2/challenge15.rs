@@ -0,0 +1,34 @@
+use crate::block::padding::{self, PaddingError};
+
+/// Strip and validate the PKCS#7 padding of a byte slice
+pub fn pkcs7_unpad<T>(bytes: T, block_len: usize) -> Result<Vec<u8>, PaddingError>
+where
+    T: AsRef<[u8]>,
+{
+    padding::unpad(bytes, block_len)
+}
+
+#[cfg(test)]
+mod test_s2_c15 {
+    use super::pkcs7_unpad;
+    use crate::block::padding::PaddingError;
+
+    #[test]
+    fn test_pkcs7_unpad() {
+        let expected = b"ICE ICE BABY";
+        let actual = pkcs7_unpad(b"ICE ICE BABY\x04\x04\x04\x04", 16).unwrap();
+        assert_eq!(&expected[..], actual);
+    }
+
+    #[test]
+    fn test_pkcs7_unpad_invalid() {
+        assert_eq!(
+            Err(PaddingError::InvalidPadByte { offset: 11 }),
+            pkcs7_unpad(b"ICE ICE BABY\x05\x05\x05\x05", 16)
+        );
+        assert_eq!(
+            Err(PaddingError::InvalidPadByte { offset: 12 }),
+            pkcs7_unpad(b"ICE ICE BABY\x01\x02\x03\x04", 16)
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use rand::Rng;
+
+use crate::block::{cbc::Cbc, BlockMode, BLOCK_SIZE};
+use crate::set2::challenge11::random_aes_key;
+
+/// Fixed data prepended to the user data
+pub static PREFIX: &[u8] = b"comment1=cooking%20MCs;userdata=";
+/// Fixed data appended to the user data
+pub static SUFFIX: &[u8] = b";comment2=%20like%20a%20pound%20of%20bacon";
+
+/// Percent-quote the metacharacters `;`, `=` and `%` in user data
+pub fn quote(input: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len());
+    for &b in input {
+        match b {
+            b';' => out.extend(b"%3B"),
+            b'=' => out.extend(b"%3D"),
+            b'%' => out.extend(b"%25"),
+            b => out.push(b),
+        }
+    }
+    out
+}
+
+/// Quote the user data and wrap it in the fixed prefix and suffix
+pub fn wrap_userdata(input: &[u8]) -> Vec<u8> {
+    [PREFIX, &quote(input)[..], SUFFIX].concat()
+}
+
+/// Check whether a decrypted `;`-separated cookie contains `admin=true`
+///
+/// Garbled blocks may hold arbitrary bytes, so the cookie isn't required to be UTF-8.
+pub fn has_admin(plaintext: &[u8]) -> bool {
+    plaintext
+        .split(|&b| b == b';')
+        .any(|pair| pair == b"admin=true")
+}
+
+/// Oracle encrypting wrapped user data using AES-CBC under a fixed random key and IV
+pub struct CbcOracle {
+    cbc: Cbc,
+}
+
+impl CbcOracle {
+    pub fn new<R>(rng: &mut R) -> Self
+    where
+        R: Rng + ?Sized,
+    {
+        let mut iv = [0; BLOCK_SIZE];
+        rng.fill(&mut iv);
+        CbcOracle {
+            // A random AES-128 key and full-block IV are always valid
+            cbc: Cbc::new(&random_aes_key(rng), &iv).unwrap(),
+        }
+    }
+
+    pub fn encrypt(&self, input: &[u8]) -> Vec<u8> {
+        self.cbc.encrypt(&wrap_userdata(input))
+    }
+
+    /// Decrypt a ciphertext and check whether it grants the admin role
+    pub fn is_admin(&self, ciphertext: &[u8]) -> bool {
+        self.cbc
+            .decrypt(ciphertext)
+            .map(|plaintext| has_admin(&plaintext))
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod test_s2_c16 {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{has_admin, quote, wrap_userdata, CbcOracle, PREFIX};
+    use crate::attack::bitflip::{Bitflip, FlipMode};
+    use crate::block::BLOCK_SIZE;
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(b"%3Badmin%3Dtrue%3B%25".to_vec(), quote(b";admin=true;%"));
+        let actual = wrap_userdata(b";admin=true");
+        assert!(actual.starts_with(PREFIX));
+        assert!(!has_admin(&actual));
+        assert!(has_admin(b"a=b;admin=true;\xff\x00"));
+    }
+
+    #[test]
+    fn test_injection_fails() {
+        let oracle = CbcOracle::new(&mut StdRng::seed_from_u64(16));
+        assert!(!oracle.is_admin(&oracle.encrypt(b";admin=true;")));
+        assert!(!oracle.is_admin(&oracle.encrypt(b"admin=true")));
+    }
+
+    #[test]
+    fn test_cbc_bitflipping() {
+        let oracle = CbcOracle::new(&mut StdRng::seed_from_u64(16));
+        let attack = Bitflip::new(
+            |input: &[u8]| oracle.encrypt(input),
+            FlipMode::Cbc {
+                block_size: BLOCK_SIZE,
+            },
+        );
+        assert_eq!(PREFIX.len(), attack.prefix_len().unwrap());
+        let forged = attack.inject(";admin=true;").unwrap();
+        assert!(oracle.is_admin(&forged));
+    }
+}
@@ -1,18 +1,17 @@
-pub fn pkcs7_pad<T>(bytes: T, block_len: usize) -> Vec<u8>
+use crate::block::padding::{self, PaddingError};
+
+/// Pad a byte slice to a whole number of blocks using PKCS#7
+pub fn pkcs7_pad<T>(bytes: T, block_len: usize) -> Result<Vec<u8>, PaddingError>
 where
     T: AsRef<[u8]>,
 {
-    let bytes = bytes.as_ref();
-    let pad_len = block_len % bytes.len();
-    let mut vec = Vec::with_capacity(bytes.len() + pad_len);
-    vec.extend(bytes);
-    vec.extend((0..pad_len).map(|_| pad_len as u8));
-    vec
+    padding::pad(bytes, block_len)
 }
 
 #[cfg(test)]
 mod test_s2_c9 {
     use super::pkcs7_pad;
+    use crate::block::padding::PaddingError;
 
     #[test]
     fn test_pkcs7_pad() {
@@ -22,18 +21,42 @@ mod test_s2_c9 {
             b'H', b'e', b'l', b'l', b'o', b' ', b'w', b'o', b'r', b'l', b'd', 5, 5, 5, 5, 5,
         ];
         let expected_len = 16;
-        let actual = pkcs7_pad(input, block_size);
+        let actual = pkcs7_pad(input, block_size).unwrap();
         assert_eq!(expected_len, actual.len());
         assert_eq!(&expected[..], actual);
     }
 
     #[test]
-    fn test_pkcs7_pad_noop() {
+    fn test_pkcs7_pad_aligned() {
         let input = [0u8; 16];
-        let expected = [0u8; 16];
+        let mut expected = vec![0u8; 16];
+        expected.extend([16; 16]);
         let block_size = 16;
-        let actual = pkcs7_pad(input, block_size);
-        assert_eq!(block_size, actual.len());
-        assert_eq!(&expected[..], actual);
+        let actual = pkcs7_pad(input, block_size).unwrap();
+        assert_eq!(2 * block_size, actual.len());
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_pkcs7_pad_longer_than_block() {
+        let input = [0u8; 20];
+        let actual = pkcs7_pad(input, 16).unwrap();
+        assert_eq!(32, actual.len());
+        assert_eq!(&[12; 12], &actual[20..]);
+    }
+
+    #[test]
+    fn test_pkcs7_pad_empty() {
+        assert_eq!(Ok(vec![16; 16]), pkcs7_pad([], 16));
+        assert_eq!(
+            Err(PaddingError::InvalidBlockLength { block_len: 0 }),
+            pkcs7_pad([], 0)
+        );
+    }
+
+    #[test]
+    fn test_pkcs7_pad_yellow_submarine() {
+        let expected = b"YELLOW SUBMARINE\x04\x04\x04\x04";
+        assert_eq!(Ok(expected.to_vec()), pkcs7_pad("YELLOW SUBMARINE", 20));
     }
 }
@@ -0,0 +1,27 @@
+use crate::block::{
+    ctr::{CounterLayout, Ctr},
+    BlockError,
+};
+
+/// Encrypt or decrypt using AES-CTR with a 64-bit nonce and counter, both little-endian
+pub fn aes_ctr(key: &[u8], nonce: u64, s: &[u8]) -> Result<Vec<u8>, BlockError> {
+    let mut ctr = Ctr::new(key, &nonce.to_le_bytes(), CounterLayout::LittleEndian64)?;
+    Ok(ctr.process(s))
+}
+
+#[cfg(test)]
+mod test_s3_c18 {
+    use super::aes_ctr;
+    use crate::codec::base64;
+
+    static INPUT: &str = "L77na/nrFsKvynd6HzOoG7GHTLXsTVu9qvY/2syLXzhPweyyMTJULu/6/kXX0KSvoOLSFQ==";
+
+    #[test]
+    fn test_aes_ctr() {
+        let input = base64::decode(INPUT).unwrap();
+        let expected = b"Yo, VIP Let's kick it Ice, Ice, baby Ice, Ice, baby ";
+        let actual = aes_ctr(b"YELLOW SUBMARINE", 0, &input).unwrap();
+        assert_eq!(&expected[..], actual);
+        assert_eq!(input, aes_ctr(b"YELLOW SUBMARINE", 0, &actual).unwrap());
+    }
+}
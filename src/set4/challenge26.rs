@@ -0,0 +1,61 @@
+use rand::Rng;
+
+use crate::block::ctr::{CounterLayout, Ctr};
+use crate::set2::challenge11::random_aes_key;
+use crate::set2::challenge16::{has_admin, wrap_userdata};
+
+/// Oracle encrypting wrapped user data using AES-CTR under a fixed random key and nonce
+pub struct CtrOracle {
+    key: [u8; 16],
+    nonce: [u8; 8],
+}
+
+impl CtrOracle {
+    pub fn new<R>(rng: &mut R) -> Self
+    where
+        R: Rng + ?Sized,
+    {
+        let key = random_aes_key(rng);
+        let mut nonce = [0; 8];
+        rng.fill(&mut nonce);
+        CtrOracle { key, nonce }
+    }
+
+    fn ctr(&self) -> Ctr {
+        // A random AES-128 key and 8-byte nonce are always valid
+        Ctr::new(&self.key, &self.nonce, CounterLayout::LittleEndian64).unwrap()
+    }
+
+    pub fn encrypt(&self, input: &[u8]) -> Vec<u8> {
+        self.ctr().process(&wrap_userdata(input))
+    }
+
+    /// Decrypt a ciphertext and check whether it grants the admin role
+    pub fn is_admin(&self, ciphertext: &[u8]) -> bool {
+        has_admin(&self.ctr().process(ciphertext))
+    }
+}
+
+#[cfg(test)]
+mod test_s4_c26 {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::CtrOracle;
+    use crate::attack::bitflip::{Bitflip, FlipMode};
+    use crate::set2::challenge16::PREFIX;
+
+    #[test]
+    fn test_injection_fails() {
+        let oracle = CtrOracle::new(&mut StdRng::seed_from_u64(26));
+        assert!(!oracle.is_admin(&oracle.encrypt(b";admin=true;")));
+    }
+
+    #[test]
+    fn test_ctr_bitflipping() {
+        let oracle = CtrOracle::new(&mut StdRng::seed_from_u64(26));
+        let attack = Bitflip::new(|input: &[u8]| oracle.encrypt(input), FlipMode::Ctr);
+        assert_eq!(PREFIX.len(), attack.prefix_len().unwrap());
+        let forged = attack.inject(";admin=true;").unwrap();
+        assert!(oracle.is_admin(&forged));
+    }
+}
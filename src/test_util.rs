@@ -0,0 +1,6 @@
+/// Deterministic pseudo-random test data
+pub(crate) fn pseudo_random_bytes(len: usize, seed: u32) -> Vec<u8> {
+    (0..len as u32)
+        .map(|i| (i.wrapping_add(seed).wrapping_mul(2_654_435_761) >> 24) as u8)
+        .collect()
+}
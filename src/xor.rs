@@ -0,0 +1,188 @@
+use std::{error::Error, fmt};
+
+pub mod crib;
+pub mod kernel;
+pub mod keysize;
+pub mod keystream;
+
+/// Error produced by xor operations on mismatched operands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XorError {
+    /// The operands have different lengths
+    LengthMismatch { left: usize, right: usize },
+    /// A repeating key must contain at least one byte
+    EmptyKey,
+}
+
+impl fmt::Display for XorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XorError::LengthMismatch { left, right } => {
+                write!(f, "operand lengths differ: {} != {}", left, right)
+            }
+            XorError::EmptyKey => write!(f, "key is empty"),
+        }
+    }
+}
+
+impl Error for XorError {}
+
+/// Xor two equally-sized byte slices
+pub fn xor<A, B>(a: A, b: B) -> Result<Vec<u8>, XorError>
+where
+    A: AsRef<[u8]>,
+    B: AsRef<[u8]>,
+{
+    let mut buf = a.as_ref().to_vec();
+    xor_into(&mut buf, b)?;
+    Ok(buf)
+}
+
+/// Xor a byte slice into an equally-sized destination slice in place
+pub fn xor_into<T>(dst: &mut [u8], src: T) -> Result<(), XorError>
+where
+    T: AsRef<[u8]>,
+{
+    let src = src.as_ref();
+    if dst.len() != src.len() {
+        return Err(XorError::LengthMismatch {
+            left: dst.len(),
+            right: src.len(),
+        });
+    }
+    kernel::xor_in_place(dst, src);
+    Ok(())
+}
+
+/// Xor a byte slice with an arbitrarily-sized key, repeating the key as needed
+pub fn repeating_key_xor<K, T>(key: K, data: T) -> Result<Vec<u8>, XorError>
+where
+    K: AsRef<[u8]>,
+    T: AsRef<[u8]>,
+{
+    let mut buf = data.as_ref().to_vec();
+    repeating_key_xor_into(key, &mut buf)?;
+    Ok(buf)
+}
+
+/// Xor a byte slice in place with an arbitrarily-sized key, repeating the key as needed
+pub fn repeating_key_xor_into<K>(key: K, data: &mut [u8]) -> Result<(), XorError>
+where
+    K: AsRef<[u8]>,
+{
+    let key = key.as_ref();
+    if key.is_empty() {
+        return Err(XorError::EmptyKey);
+    }
+    kernel::xor_repeating_in_place(data, key);
+    Ok(())
+}
+
+/// Xor every byte of a byte slice with a single-byte key
+pub fn single_byte_xor<T>(key: u8, data: T) -> Vec<u8>
+where
+    T: AsRef<[u8]>,
+{
+    let mut buf = data.as_ref().to_vec();
+    single_byte_xor_into(key, &mut buf);
+    buf
+}
+
+/// Xor every byte of a byte slice in place with a single-byte key
+pub fn single_byte_xor_into(key: u8, data: &mut [u8]) {
+    kernel::xor_byte_in_place(data, key);
+}
+
+/// Xor operations on byte slices
+pub trait Xor {
+    /// Xor with an equally-sized byte slice
+    fn xor(&self, other: &[u8]) -> Result<Vec<u8>, XorError>;
+    /// Xor with a repeating key
+    fn xor_repeating(&self, key: &[u8]) -> Result<Vec<u8>, XorError>;
+    /// Xor every byte with a single-byte key
+    fn xor_byte(&self, key: u8) -> Vec<u8>;
+}
+
+impl Xor for [u8] {
+    fn xor(&self, other: &[u8]) -> Result<Vec<u8>, XorError> {
+        xor(self, other)
+    }
+
+    fn xor_repeating(&self, key: &[u8]) -> Result<Vec<u8>, XorError> {
+        repeating_key_xor(key, self)
+    }
+
+    fn xor_byte(&self, key: u8) -> Vec<u8> {
+        single_byte_xor(key, self)
+    }
+}
+
+#[cfg(test)]
+mod test_xor {
+    use super::{
+        repeating_key_xor, repeating_key_xor_into, single_byte_xor, single_byte_xor_into, xor,
+        xor_into, Xor, XorError,
+    };
+
+    #[test]
+    fn test_xor() {
+        let a = [0xAA, 0xBB, 0xCC];
+        let b = [0xC1, 0xB2, 0xA3];
+        let expected = [0x6B, 0x09, 0x6F];
+        assert_eq!(Ok(expected.to_vec()), xor(a, b));
+        assert_eq!(Ok(Vec::new()), xor([], []));
+    }
+
+    #[test]
+    fn test_xor_length_mismatch() {
+        let expected = XorError::LengthMismatch { left: 3, right: 4 };
+        assert_eq!(Err(expected), xor([0; 3], [0; 4]));
+    }
+
+    #[test]
+    fn test_xor_into() {
+        let mut dst = [0xAA, 0xBB, 0xCC];
+        xor_into(&mut dst, [0xC1, 0xB2, 0xA3]).unwrap();
+        assert_eq!([0x6B, 0x09, 0x6F], dst);
+        let expected = XorError::LengthMismatch { left: 3, right: 2 };
+        assert_eq!(Err(expected), xor_into(&mut dst, [0; 2]));
+        assert_eq!([0x6B, 0x09, 0x6F], dst);
+    }
+
+    #[test]
+    fn test_repeating_key_xor() {
+        let expected = b"$\x08\r\x03\x03M\x16\x00\x1e\x01\x05";
+        assert_eq!(
+            Ok(expected.to_vec()),
+            repeating_key_xor("lmao", "Hello world")
+        );
+        let mut data = *b"Hello world";
+        repeating_key_xor_into("lmao", &mut data).unwrap();
+        assert_eq!(expected, &data);
+    }
+
+    #[test]
+    fn test_repeating_key_xor_empty_key() {
+        assert_eq!(Err(XorError::EmptyKey), repeating_key_xor([], [1, 2, 3]));
+        assert_eq!(Err(XorError::EmptyKey), repeating_key_xor_into([], &mut []));
+    }
+
+    #[test]
+    fn test_single_byte_xor() {
+        assert_eq!(
+            vec![0x7f, 0x80, 0x00],
+            single_byte_xor(0xff, [0x80, 0x7f, 0xff])
+        );
+        let mut data = [0x80, 0x7f, 0xff];
+        single_byte_xor_into(0xff, &mut data);
+        assert_eq!([0x7f, 0x80, 0x00], data);
+    }
+
+    #[test]
+    fn test_xor_trait() {
+        let data = b"Hello".to_vec();
+        assert_eq!(Ok(vec![0; 5]), data.xor(b"Hello"));
+        assert_eq!(Ok(b"$\x08\r\x03\x03".to_vec()), data.xor_repeating(b"lmao"));
+        assert_eq!(data, data.xor_byte(0x20).xor_byte(0x20));
+    }
+}
@@ -0,0 +1,370 @@
+use std::{error::Error, fmt};
+
+use super::kernel;
+use crate::scoring::Scorer;
+
+/// Error produced when locking in recovered key bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CribError {
+    /// The key byte at the given offset was already locked to a different value
+    Conflict {
+        offset: usize,
+        locked: u8,
+        proposed: u8,
+    },
+    /// The fragment starting at the offset doesn't fit in the ciphertext
+    OutOfRange { offset: usize, len: usize },
+}
+
+impl fmt::Display for CribError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CribError::Conflict {
+                offset,
+                locked,
+                proposed,
+            } => write!(
+                f,
+                "key byte at offset {} is locked to {:#04x}, not {:#04x}",
+                offset, locked, proposed
+            ),
+            CribError::OutOfRange { offset, len } => {
+                write!(f, "offset {} out of range for length {}", offset, len)
+            }
+        }
+    }
+}
+
+impl Error for CribError {}
+
+/// Result of placing a crib at a single offset of a ciphertext
+#[derive(Debug, Clone, PartialEq)]
+pub struct CribMatch {
+    pub offset: usize,
+    /// The ciphertext xored with the crib at the offset
+    ///
+    /// For a single ciphertext this is a fragment of the key, while for two
+    /// ciphertexts sharing a keystream it is a fragment of the other plaintext.
+    pub fragment: Vec<u8>,
+    pub score: f32,
+}
+
+/// Slide a known crib across a ciphertext, returning the fragment revealed at
+/// each offset ranked from most to least plausible by the specified scorer.
+///
+/// Returns no matches if the crib is empty or longer than the ciphertext.
+pub fn drag_crib<C, T, S>(ciphertext: C, crib: T, scorer: &S) -> Vec<CribMatch>
+where
+    C: AsRef<[u8]>,
+    T: AsRef<[u8]>,
+    S: Scorer + ?Sized,
+{
+    let crib = crib.as_ref();
+    if crib.is_empty() {
+        return Vec::new();
+    }
+    let mut matches = ciphertext
+        .as_ref()
+        .windows(crib.len())
+        .enumerate()
+        .map(|(offset, window)| {
+            let mut fragment = window.to_vec();
+            kernel::xor_in_place(&mut fragment, crib);
+            CribMatch {
+                offset,
+                score: scorer.score(&fragment),
+                fragment,
+            }
+        })
+        .collect::<Vec<_>>();
+    // Stable sort, so that ties are broken by the lowest offset
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+    matches
+}
+
+/// Slide a known crib across the xor of two ciphertexts encrypted with the
+/// same keystream, revealing fragments of the other plaintext.
+///
+/// Only the common prefix of both ciphertexts is searched.
+pub fn drag_crib_pair<A, B, T, S>(a: A, b: B, crib: T, scorer: &S) -> Vec<CribMatch>
+where
+    A: AsRef<[u8]>,
+    B: AsRef<[u8]>,
+    T: AsRef<[u8]>,
+    S: Scorer + ?Sized,
+{
+    let (a, b) = (a.as_ref(), b.as_ref());
+    let mut combined = a[..a.len().min(b.len())].to_vec();
+    kernel::xor_in_place(&mut combined, b);
+    drag_crib(combined, crib, scorer)
+}
+
+/// Partially recovered key, built up incrementally from known plaintext
+///
+/// Key bytes are addressed by their position in the key. A ciphertext offset
+/// maps to the key byte at that offset modulo the key length, so the same
+/// type serves both repeating keys and keystreams as long as the ciphertext.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialKey {
+    bytes: Vec<Option<u8>>,
+}
+
+impl PartialKey {
+    /// Create a key of the specified length with no known bytes
+    pub fn new(len: usize) -> Self {
+        PartialKey {
+            bytes: vec![None; len],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Key byte at the specified position, wrapping around the key length, if known
+    pub fn get(&self, offset: usize) -> Option<u8> {
+        let offset = offset.checked_rem(self.bytes.len())?;
+        self.bytes[offset]
+    }
+
+    /// Number of known key bytes
+    pub fn known(&self) -> usize {
+        self.bytes.iter().filter(|b| b.is_some()).count()
+    }
+
+    /// The complete key, if every byte is known
+    pub fn to_key(&self) -> Option<Vec<u8>> {
+        self.bytes.iter().copied().collect()
+    }
+
+    /// Lock in key bytes starting at the specified position
+    ///
+    /// Locking is all-or-nothing: if any byte conflicts with a previously
+    /// locked byte, the key is left untouched.
+    pub fn lock<T>(&mut self, offset: usize, fragment: T) -> Result<(), CribError>
+    where
+        T: AsRef<[u8]>,
+    {
+        let fragment = fragment.as_ref();
+        if self.bytes.is_empty() && !fragment.is_empty() {
+            return Err(CribError::OutOfRange { offset, len: 0 });
+        }
+        // Stage the bytes, so that a fragment wrapping past the key length is
+        // also checked against its own earlier bytes
+        let mut staged = self.bytes.clone();
+        for (i, &proposed) in fragment.iter().enumerate() {
+            let offset = (offset % staged.len() + i) % staged.len();
+            match staged[offset] {
+                Some(locked) if locked != proposed => {
+                    return Err(CribError::Conflict {
+                        offset,
+                        locked,
+                        proposed,
+                    })
+                }
+                _ => staged[offset] = Some(proposed),
+            }
+        }
+        self.bytes = staged;
+        Ok(())
+    }
+
+    /// Lock in the key bytes revealed by a crib placed at the specified ciphertext offset
+    pub fn lock_crib<C, T>(
+        &mut self,
+        ciphertext: C,
+        offset: usize,
+        crib: T,
+    ) -> Result<(), CribError>
+    where
+        C: AsRef<[u8]>,
+        T: AsRef<[u8]>,
+    {
+        let (ciphertext, crib) = (ciphertext.as_ref(), crib.as_ref());
+        let window = offset
+            .checked_add(crib.len())
+            .and_then(|end| ciphertext.get(offset..end))
+            .ok_or(CribError::OutOfRange {
+                offset,
+                len: ciphertext.len(),
+            })?;
+        let mut fragment = window.to_vec();
+        kernel::xor_in_place(&mut fragment, crib);
+        self.lock(offset, fragment)
+    }
+
+    /// Forget the key byte at the specified position, wrapping around the key length
+    pub fn unlock(&mut self, offset: usize) {
+        if let Some(offset) = offset.checked_rem(self.bytes.len()) {
+            self.bytes[offset] = None;
+        }
+    }
+
+    /// Decrypt a ciphertext with the known key bytes, leaving unknown bytes as `None`
+    pub fn decrypt<T>(&self, ciphertext: T) -> Vec<Option<u8>>
+    where
+        T: AsRef<[u8]>,
+    {
+        ciphertext
+            .as_ref()
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let key = self.bytes.get(i.checked_rem(self.bytes.len())?)?;
+                key.map(|k| c ^ k)
+            })
+            .collect()
+    }
+
+    /// Decrypt a ciphertext with the known key bytes, replacing unknown bytes with a placeholder
+    pub fn decrypt_lossy<T>(&self, ciphertext: T, placeholder: u8) -> Vec<u8>
+    where
+        T: AsRef<[u8]>,
+    {
+        self.decrypt(ciphertext)
+            .into_iter()
+            .map(|b| b.unwrap_or(placeholder))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test_crib {
+    use super::{drag_crib, drag_crib_pair, CribError, PartialKey};
+    use crate::scoring::PrintableRatio;
+    use crate::xor::{repeating_key_xor, xor};
+
+    static KEYSTREAM: &[u8] = b"\x8f\x1d\x3c\xa2\x77\x5e\xe0\x09\x44\xbb\x61\xd3\x2a\x90\x0e\x7c\
+\xf5\x38\xc6\x13\x5a\xae\x81\x27\x6d\xe4\x02\x9f\xb7\x4b\x31\xd8\x56\x0c";
+    static PLAINTEXT_A: &[u8] = b"Meet me at the old mill by dawn!!";
+    static PLAINTEXT_B: &[u8] = b"Bring the maps and all the money.";
+
+    #[test]
+    fn test_drag_crib_key_fragment() {
+        let ciphertext = repeating_key_xor("SECRET", b"%PDF-1.7 some document body").unwrap();
+        let actual = drag_crib(&ciphertext, "%PDF-", &PrintableRatio);
+        assert_eq!(ciphertext.len() - 4, actual.len());
+        let header = actual.iter().find(|m| m.offset == 0).unwrap();
+        assert_eq!(b"SECRE", &header.fragment[..]);
+        assert_eq!(1.0, header.score);
+        assert!(actual.windows(2).all(|w| w[0].score >= w[1].score));
+    }
+
+    #[test]
+    fn test_drag_crib_pair() {
+        let a = xor(PLAINTEXT_A, &KEYSTREAM[..PLAINTEXT_A.len()]).unwrap();
+        let b = xor(PLAINTEXT_B, &KEYSTREAM[..PLAINTEXT_B.len()]).unwrap();
+        let actual = drag_crib_pair(&a, &b, " the ", &PrintableRatio);
+        let printable = actual
+            .iter()
+            .take_while(|m| m.score == 1.0)
+            .map(|m| (m.offset, &m.fragment[..]))
+            .collect::<Vec<_>>();
+        // " the " occurs in the first plaintext at offset 10, revealing the second
+        assert!(printable.contains(&(10, &PLAINTEXT_B[10..15])));
+        // ...and in the second plaintext at offset 5, revealing the first
+        assert!(printable.contains(&(5, &PLAINTEXT_A[5..10])));
+    }
+
+    #[test]
+    fn test_drag_crib_no_matches() {
+        assert!(drag_crib(b"abc", "", &PrintableRatio).is_empty());
+        assert!(drag_crib(b"abc", "abcd", &PrintableRatio).is_empty());
+    }
+
+    #[test]
+    fn test_partial_key_lock_crib() {
+        let a = xor(PLAINTEXT_A, &KEYSTREAM[..PLAINTEXT_A.len()]).unwrap();
+        let mut key = PartialKey::new(a.len());
+        key.lock_crib(&a, 0, "Meet").unwrap();
+        key.lock_crib(&a, 10, " the ").unwrap();
+        assert_eq!(9, key.known());
+        assert_eq!(Some(KEYSTREAM[12]), key.get(12));
+        assert_eq!(None, key.get(4));
+        assert_eq!(
+            b"Meet?????? the ??????????????????",
+            &key.decrypt_lossy(&a, b'?')[..]
+        );
+        assert_eq!(None, key.to_key());
+        key.unlock(12);
+        assert_eq!(None, key.decrypt(&a)[12]);
+        key.lock_crib(&a, 0, PLAINTEXT_A).unwrap();
+        assert_eq!(Some(KEYSTREAM[..a.len()].to_vec()), key.to_key());
+    }
+
+    #[test]
+    fn test_partial_key_repeating() {
+        let ciphertext = repeating_key_xor("KEY", b"hello world").unwrap();
+        let mut key = PartialKey::new(3);
+        // Key bytes wrap around the key length
+        key.lock_crib(&ciphertext, 4, "o w").unwrap();
+        assert_eq!(Some(b"KEY".to_vec()), key.to_key());
+        assert_eq!(b"hello world", &key.decrypt_lossy(&ciphertext, b'?')[..]);
+        assert_eq!(Some(b'E'), key.get(4));
+        key.unlock(4);
+        assert_eq!(None, key.get(1));
+        assert_eq!(None, PartialKey::new(0).get(0));
+        // `usize::MAX` is a multiple of three
+        key.lock(usize::MAX, "KE").unwrap();
+        assert_eq!(Some(b"KEY".to_vec()), key.to_key());
+    }
+
+    #[test]
+    fn test_partial_key_crib_longer_than_key() {
+        let ciphertext = repeating_key_xor("KEY", b"hello world").unwrap();
+        let mut key = PartialKey::new(3);
+        // A crib at the wrong offset conflicts with itself once it wraps
+        assert!(matches!(
+            key.lock_crib(&ciphertext, 0, "xyzw"),
+            Err(CribError::Conflict { offset: 0, .. })
+        ));
+        assert_eq!(0, key.known());
+        assert_eq!(
+            Err(CribError::Conflict {
+                offset: 0,
+                locked: 1,
+                proposed: 9
+            }),
+            key.lock(0, [1, 2, 3, 9, 9])
+        );
+        assert_eq!(0, key.known());
+        // A consistent crib longer than the key is accepted
+        key.lock_crib(&ciphertext, 0, "hello").unwrap();
+        assert_eq!(Some(b"KEY".to_vec()), key.to_key());
+    }
+
+    #[test]
+    fn test_partial_key_errors() {
+        let mut key = PartialKey::new(4);
+        key.lock(0, [1, 2]).unwrap();
+        assert_eq!(
+            Err(CribError::Conflict {
+                offset: 1,
+                locked: 2,
+                proposed: 3
+            }),
+            key.lock(1, [3, 4])
+        );
+        // The conflicting lock left the key untouched
+        assert_eq!(None, key.get(2));
+        assert_eq!(
+            Err(CribError::OutOfRange { offset: 2, len: 3 }),
+            key.lock_crib(b"abc", 2, "xyz")
+        );
+        assert_eq!(
+            Err(CribError::OutOfRange {
+                offset: usize::MAX,
+                len: 3
+            }),
+            key.lock_crib(b"abc", usize::MAX, "x")
+        );
+        assert_eq!(
+            Err(CribError::OutOfRange { offset: 0, len: 0 }),
+            PartialKey::new(0).lock(0, [1])
+        );
+    }
+}
@@ -0,0 +1,143 @@
+/// Number of bytes processed per word
+const WORD: usize = std::mem::size_of::<u64>();
+
+/// Load a native-endian word from an 8-byte chunk
+#[inline(always)]
+fn load(chunk: &[u8]) -> u64 {
+    u64::from_ne_bytes(chunk.try_into().unwrap())
+}
+
+/// Xor `src` into `dst` a word at a time
+///
+/// Only the common prefix of both slices is processed.
+pub fn xor_in_place(dst: &mut [u8], src: &[u8]) {
+    let len = dst.len().min(src.len());
+    let (dst, src) = (&mut dst[..len], &src[..len]);
+    let mut dst_words = dst.chunks_exact_mut(WORD);
+    let mut src_words = src.chunks_exact(WORD);
+    for (d, s) in (&mut dst_words).zip(&mut src_words) {
+        d.copy_from_slice(&(load(d) ^ load(s)).to_ne_bytes());
+    }
+    // Portable fallback for the trailing bytes
+    for (d, s) in dst_words
+        .into_remainder()
+        .iter_mut()
+        .zip(src_words.remainder())
+    {
+        *d ^= s;
+    }
+}
+
+/// Xor a repeating key into `dst` a word at a time
+pub fn xor_repeating_in_place(dst: &mut [u8], key: &[u8]) {
+    match key.len() {
+        0 => {}
+        1 => xor_byte_in_place(dst, key[0]),
+        len => {
+            // Repeat the key into a block that is a whole number of words and keys long
+            let block = key
+                .iter()
+                .cycle()
+                .take(len * WORD)
+                .copied()
+                .collect::<Vec<_>>();
+            for chunk in dst.chunks_mut(block.len()) {
+                xor_in_place(chunk, &block);
+            }
+        }
+    }
+}
+
+/// Xor a single-byte key into `dst` a word at a time
+pub fn xor_byte_in_place(dst: &mut [u8], key: u8) {
+    let word = u64::from_ne_bytes([key; WORD]);
+    let mut words = dst.chunks_exact_mut(WORD);
+    for d in &mut words {
+        d.copy_from_slice(&(load(d) ^ word).to_ne_bytes());
+    }
+    for d in words.into_remainder() {
+        *d ^= key;
+    }
+}
+
+/// Count the differing bits between two slices a word at a time
+///
+/// Only the common prefix of both slices is compared.
+pub fn xor_popcount(a: &[u8], b: &[u8]) -> usize {
+    let len = a.len().min(b.len());
+    let (a, b) = (&a[..len], &b[..len]);
+    let a_words = a.chunks_exact(WORD);
+    let b_words = b.chunks_exact(WORD);
+    let tail = a_words
+        .remainder()
+        .iter()
+        .zip(b_words.remainder())
+        .map(|(x, y)| (x ^ y).count_ones() as usize)
+        .sum::<usize>();
+    a_words
+        .zip(b_words)
+        .map(|(x, y)| (load(x) ^ load(y)).count_ones() as usize)
+        .sum::<usize>()
+        + tail
+}
+
+#[cfg(test)]
+mod test_xor_kernel {
+    use super::{xor_byte_in_place, xor_in_place, xor_popcount, xor_repeating_in_place};
+    use crate::test_util::pseudo_random_bytes as data;
+
+    #[test]
+    fn test_xor_in_place_matches_bytewise() {
+        for len in 0..40 {
+            let (a, b) = (data(len, 1), data(len, 2));
+            let expected = a.iter().zip(&b).map(|(x, y)| x ^ y).collect::<Vec<_>>();
+            let mut actual = a.clone();
+            xor_in_place(&mut actual, &b);
+            assert_eq!(expected, actual, "len {}", len);
+        }
+    }
+
+    #[test]
+    fn test_xor_repeating_in_place_matches_bytewise() {
+        for key_len in 0..12 {
+            let key = data(key_len, 3);
+            for len in [0, 1, 7, 8, 9, 63, 64, 65, 200] {
+                let input = data(len, 4);
+                let expected = input
+                    .iter()
+                    .zip(key.iter().cycle())
+                    .map(|(x, k)| x ^ k)
+                    .chain(input.iter().copied().filter(|_| key.is_empty()))
+                    .collect::<Vec<_>>();
+                let mut actual = input.clone();
+                xor_repeating_in_place(&mut actual, &key);
+                assert_eq!(expected, actual, "key {} len {}", key_len, len);
+            }
+        }
+    }
+
+    #[test]
+    fn test_xor_byte_in_place_matches_bytewise() {
+        for len in 0..20 {
+            let input = data(len, 5);
+            let expected = input.iter().map(|x| x ^ 0xA5).collect::<Vec<_>>();
+            let mut actual = input.clone();
+            xor_byte_in_place(&mut actual, 0xA5);
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn test_xor_popcount_matches_bytewise() {
+        for len in 0..40 {
+            let (a, b) = (data(len, 6), data(len + 3, 7));
+            let expected = a
+                .iter()
+                .zip(&b)
+                .map(|(x, y)| (x ^ y).count_ones() as usize)
+                .sum::<usize>();
+            assert_eq!(expected, xor_popcount(&a, &b), "len {}", len);
+        }
+        assert_eq!(37, xor_popcount(b"this is a test", b"wokka wokka!!!"));
+    }
+}
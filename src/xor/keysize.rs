@@ -0,0 +1,185 @@
+use std::ops::RangeInclusive;
+
+use super::kernel;
+
+/// Statistic used to rank candidate key sizes of a repeating-key-xor-encrypted ciphertext
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeySizeMethod {
+    /// Mean normalized Hamming distance between all adjacent key-sized blocks
+    HammingDistance,
+    /// Mean index of coincidence of the transposed single-byte-xor columns
+    IndexOfCoincidence,
+    /// Rate of equal bytes when the ciphertext is shifted by the key size
+    Autocorrelation,
+}
+
+/// Score of a single candidate key size
+///
+/// Higher scores indicate more probable key sizes. Scores are only comparable
+/// between results of the same method.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeySizeScore {
+    pub key_size: usize,
+    pub score: f32,
+}
+
+/// Estimator of the key size of repeating-key-xor-encrypted ciphertexts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeySizeEstimator {
+    pub method: KeySizeMethod,
+    /// Candidate key sizes, in bytes
+    pub range: RangeInclusive<usize>,
+}
+
+impl KeySizeEstimator {
+    /// Default range of candidate key sizes
+    pub const DEFAULT_RANGE: RangeInclusive<usize> = 2..=40;
+
+    pub fn new(method: KeySizeMethod) -> Self {
+        KeySizeEstimator {
+            method,
+            range: KeySizeEstimator::DEFAULT_RANGE,
+        }
+    }
+
+    pub fn with_range(self, range: RangeInclusive<usize>) -> Self {
+        KeySizeEstimator { range, ..self }
+    }
+
+    /// Rank all candidate key sizes from most to least probable
+    ///
+    /// Key sizes that the ciphertext is too short to evaluate are left out, so
+    /// the result is empty if no candidate can be evaluated.
+    pub fn estimate<T>(&self, s: T) -> Vec<KeySizeScore>
+    where
+        T: AsRef<[u8]>,
+    {
+        let s = s.as_ref();
+        let mut scores = self
+            .range
+            .clone()
+            .filter(|&key_size| key_size > 0)
+            .filter_map(|key_size| {
+                let score = match self.method {
+                    KeySizeMethod::HammingDistance => hamming_distance_score(s, key_size),
+                    KeySizeMethod::IndexOfCoincidence => index_of_coincidence_score(s, key_size),
+                    KeySizeMethod::Autocorrelation => autocorrelation_score(s, key_size),
+                }?;
+                Some(KeySizeScore { key_size, score })
+            })
+            .collect::<Vec<_>>();
+        // Stable sort, so that ties are broken by the smallest key size
+        scores.sort_by(|a, b| b.score.total_cmp(&a.score));
+        scores
+    }
+}
+
+impl Default for KeySizeEstimator {
+    fn default() -> Self {
+        KeySizeEstimator::new(KeySizeMethod::HammingDistance)
+    }
+}
+
+/// Negated mean Hamming distance per byte between adjacent blocks
+fn hamming_distance_score(s: &[u8], key_size: usize) -> Option<f32> {
+    let blocks = s.chunks_exact(key_size).collect::<Vec<_>>();
+    if blocks.len() < 2 {
+        return None;
+    }
+    let distance = blocks
+        .windows(2)
+        .map(|w| kernel::xor_popcount(w[0], w[1]))
+        .sum::<usize>();
+    Some(-(distance as f32) / ((blocks.len() - 1) * key_size) as f32)
+}
+
+/// Mean index of coincidence of the columns encrypted with the same key byte
+fn index_of_coincidence_score(s: &[u8], key_size: usize) -> Option<f32> {
+    // Every column needs at least two bytes to contain a coincidence
+    if s.len() < 2 * key_size {
+        return None;
+    }
+    let total = (0..key_size)
+        .map(|column| {
+            let mut counts = [0usize; 256];
+            let mut len = 0;
+            for &b in s.iter().skip(column).step_by(key_size) {
+                counts[b as usize] += 1;
+                len += 1;
+            }
+            let coincidences = counts
+                .iter()
+                .map(|n| n * n.saturating_sub(1))
+                .sum::<usize>();
+            coincidences as f32 / (len * (len - 1)) as f32
+        })
+        .sum::<f32>();
+    Some(total / key_size as f32)
+}
+
+/// Rate of positions holding the same byte as the position one key size further
+fn autocorrelation_score(s: &[u8], key_size: usize) -> Option<f32> {
+    if s.len() <= key_size {
+        return None;
+    }
+    let matches = s.iter().zip(&s[key_size..]).filter(|(a, b)| a == b).count();
+    Some(matches as f32 / (s.len() - key_size) as f32)
+}
+
+#[cfg(test)]
+mod test_keysize {
+    use super::{KeySizeEstimator, KeySizeMethod};
+    use crate::codec::base64;
+
+    static METHODS: [KeySizeMethod; 3] = [
+        KeySizeMethod::HammingDistance,
+        KeySizeMethod::IndexOfCoincidence,
+        KeySizeMethod::Autocorrelation,
+    ];
+
+    fn ciphertext() -> Vec<u8> {
+        base64::decode(include_str!("../data/s1c6.txt")).unwrap()
+    }
+
+    #[test]
+    fn test_estimate() {
+        let input = ciphertext();
+        for method in METHODS {
+            let actual = KeySizeEstimator::new(method).estimate(&input);
+            assert_eq!(39, actual.len(), "{:?}", method);
+            assert_eq!(29, actual[0].key_size, "{:?}", method);
+            assert!(actual.windows(2).all(|w| w[0].score >= w[1].score));
+        }
+    }
+
+    #[test]
+    fn test_estimate_with_range() {
+        let input = ciphertext();
+        let estimator = KeySizeEstimator::default().with_range(10..=20);
+        let actual = estimator.estimate(&input);
+        assert_eq!(11, actual.len());
+        assert!(actual.iter().all(|s| (10..=20).contains(&s.key_size)));
+        // Multiples of the key size are just as likely as the key size itself
+        let estimator =
+            KeySizeEstimator::new(KeySizeMethod::IndexOfCoincidence).with_range(30..=60);
+        assert_eq!(58, estimator.estimate(&input)[0].key_size);
+    }
+
+    #[test]
+    fn test_estimate_short_input() {
+        for method in METHODS {
+            let estimator = KeySizeEstimator::new(method);
+            assert!(estimator.estimate(b"").is_empty());
+            assert!(estimator.estimate(b"a").is_empty());
+            assert!(!estimator.estimate(b"short input").is_empty());
+        }
+        // Two full blocks are needed to compare their Hamming distance
+        let actual = KeySizeEstimator::default().estimate(b"short input");
+        assert_eq!(4, actual.len());
+        assert!(actual.iter().all(|s| s.key_size <= 5));
+        let estimator = KeySizeEstimator::default().with_range(0..=0);
+        assert!(estimator
+            .estimate(b"zero-sized keys are skipped")
+            .is_empty());
+    }
+}
@@ -0,0 +1,283 @@
+use std::{error::Error, fmt};
+
+use crate::scoring::Scorer;
+use crate::set1::challenge3::{confidence_margin, rank_single_byte_xor_keys};
+
+/// Error produced when correcting a recovered keystream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeystreamError {
+    /// There is no ciphertext with the given index
+    CiphertextOutOfRange { index: usize, count: usize },
+    /// The plaintext starting at the offset doesn't fit in the ciphertext or keystream
+    OffsetOutOfRange { offset: usize, len: usize },
+}
+
+impl fmt::Display for KeystreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeystreamError::CiphertextOutOfRange { index, count } => {
+                write!(
+                    f,
+                    "ciphertext {} out of range for {} ciphertexts",
+                    index, count
+                )
+            }
+            KeystreamError::OffsetOutOfRange { offset, len } => {
+                write!(f, "offset {} out of range for length {}", offset, len)
+            }
+        }
+    }
+}
+
+impl Error for KeystreamError {}
+
+/// Recovered state of a single keystream byte
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeystreamByte {
+    pub value: u8,
+    /// Number of ciphertexts long enough to cover this byte
+    pub samples: usize,
+    /// Score difference to the second best guess, see [`confidence_margin`]
+    pub margin: Option<f32>,
+    /// Whether the byte was set manually and is no longer guessed by [`KeystreamSolver::solve`]
+    pub locked: bool,
+}
+
+/// Solver for many ciphertexts encrypted under the same keystream
+///
+/// Every keystream byte is guessed independently by cracking the column of
+/// ciphertext bytes at its offset as a single-byte-xor cipher. Ciphertexts may
+/// have different lengths, so later columns hold fewer bytes and are less
+/// reliable. Guesses can be corrected by hand, after which they are locked.
+#[derive(Debug, Clone)]
+pub struct KeystreamSolver {
+    ciphertexts: Vec<Vec<u8>>,
+    keystream: Vec<KeystreamByte>,
+}
+
+impl KeystreamSolver {
+    /// Create a solver with an all-zero keystream as long as the longest ciphertext
+    pub fn new<T>(ciphertexts: &[T]) -> Self
+    where
+        T: AsRef<[u8]>,
+    {
+        let ciphertexts = ciphertexts
+            .iter()
+            .map(|c| c.as_ref().to_vec())
+            .collect::<Vec<_>>();
+        let len = ciphertexts.iter().map(Vec::len).max().unwrap_or(0);
+        let keystream = (0..len)
+            .map(|offset| KeystreamByte {
+                value: 0,
+                samples: ciphertexts.iter().filter(|c| c.len() > offset).count(),
+                margin: None,
+                locked: false,
+            })
+            .collect();
+        KeystreamSolver {
+            ciphertexts,
+            keystream,
+        }
+    }
+
+    /// Guess every unlocked keystream byte using the specified scorer
+    pub fn solve<S>(&mut self, scorer: &S)
+    where
+        S: Scorer + ?Sized,
+    {
+        for (offset, byte) in self.keystream.iter_mut().enumerate() {
+            if byte.locked {
+                continue;
+            }
+            let column = self
+                .ciphertexts
+                .iter()
+                .filter_map(|c| c.get(offset).copied())
+                .collect::<Vec<_>>();
+            let candidates = rank_single_byte_xor_keys(&column, scorer);
+            byte.value = candidates[0].key;
+            byte.margin = confidence_margin(&candidates);
+        }
+    }
+
+    /// The current keystream guess
+    pub fn keystream(&self) -> Vec<u8> {
+        self.keystream.iter().map(|b| b.value).collect()
+    }
+
+    /// Detailed state of every keystream byte
+    pub fn bytes(&self) -> &[KeystreamByte] {
+        &self.keystream
+    }
+
+    /// Manually set and lock a keystream byte
+    pub fn set_keystream_byte(&mut self, offset: usize, value: u8) -> Result<(), KeystreamError> {
+        let len = self.keystream.len();
+        let byte = self
+            .keystream
+            .get_mut(offset)
+            .ok_or(KeystreamError::OffsetOutOfRange { offset, len })?;
+        byte.value = value;
+        byte.margin = None;
+        byte.locked = true;
+        Ok(())
+    }
+
+    /// Lock the keystream bytes that decrypt a ciphertext to the known plaintext at the offset
+    pub fn set_plaintext<T>(
+        &mut self,
+        index: usize,
+        offset: usize,
+        plaintext: T,
+    ) -> Result<(), KeystreamError>
+    where
+        T: AsRef<[u8]>,
+    {
+        let plaintext = plaintext.as_ref();
+        let ciphertext =
+            self.ciphertexts
+                .get(index)
+                .ok_or(KeystreamError::CiphertextOutOfRange {
+                    index,
+                    count: self.ciphertexts.len(),
+                })?;
+        let window = offset
+            .checked_add(plaintext.len())
+            .and_then(|end| ciphertext.get(offset..end))
+            .ok_or(KeystreamError::OffsetOutOfRange {
+                offset,
+                len: ciphertext.len(),
+            })?
+            .to_vec();
+        for (i, (c, p)) in window.iter().zip(plaintext).enumerate() {
+            self.set_keystream_byte(offset + i, c ^ p)?;
+        }
+        Ok(())
+    }
+
+    /// Unlock a keystream byte, so that it is guessed again by the next call to [`KeystreamSolver::solve`]
+    pub fn unlock(&mut self, offset: usize) {
+        if let Some(byte) = self.keystream.get_mut(offset) {
+            byte.locked = false;
+        }
+    }
+
+    /// Decrypt the ciphertext with the given index using the current keystream guess
+    pub fn plaintext(&self, index: usize) -> Option<Vec<u8>> {
+        let ciphertext = self.ciphertexts.get(index)?;
+        Some(
+            ciphertext
+                .iter()
+                .zip(&self.keystream)
+                .map(|(c, k)| c ^ k.value)
+                .collect(),
+        )
+    }
+
+    /// Decrypt all ciphertexts using the current keystream guess
+    pub fn plaintexts(&self) -> Vec<Vec<u8>> {
+        (0..self.ciphertexts.len())
+            .filter_map(|i| self.plaintext(i))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test_keystream {
+    use super::{KeystreamError, KeystreamSolver};
+    use crate::codec::base64;
+    use crate::scoring::LetterFrequency;
+    use crate::test_util::pseudo_random_bytes;
+    use crate::xor::{repeating_key_xor, xor};
+
+    /// Distinct lines of English text of various lengths
+    fn plaintexts() -> Vec<Vec<u8>> {
+        let ciphertext = base64::decode(include_str!("../data/s1c6.txt")).unwrap();
+        let plaintext = repeating_key_xor("Terminator X: Bring the noise", ciphertext).unwrap();
+        let mut lines = plaintext
+            .split(|&b| b == b'\n')
+            .filter(|line| line.len() > 8)
+            .map(|line| line.to_vec())
+            .collect::<Vec<_>>();
+        lines.sort();
+        lines.dedup();
+        lines
+    }
+
+    fn encrypt(plaintexts: &[Vec<u8>], keystream: &[u8]) -> Vec<Vec<u8>> {
+        plaintexts
+            .iter()
+            .map(|p| xor(p, &keystream[..p.len()]).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_solve() {
+        let plaintexts = plaintexts();
+        let max_len = plaintexts.iter().map(Vec::len).max().unwrap();
+        let expected = pseudo_random_bytes(max_len, 0);
+        let mut solver = KeystreamSolver::new(&encrypt(&plaintexts, &expected));
+        solver.solve(&LetterFrequency::english());
+        let actual = solver.keystream();
+        assert_eq!(max_len, actual.len());
+        // Columns covered by plenty of ciphertexts are recovered correctly
+        for (offset, byte) in solver.bytes().iter().enumerate() {
+            if byte.samples >= 20 {
+                assert_eq!(expected[offset], actual[offset], "offset {}", offset);
+            }
+        }
+        assert!(solver.bytes().iter().any(|b| b.samples < 20));
+        assert_eq!(plaintexts.len(), solver.bytes()[0].samples);
+        assert_eq!(1, solver.bytes()[max_len - 1].samples);
+    }
+
+    #[test]
+    fn test_manual_correction() {
+        let plaintexts = plaintexts();
+        let max_len = plaintexts.iter().map(Vec::len).max().unwrap();
+        let expected = pseudo_random_bytes(max_len, 0);
+        let mut solver = KeystreamSolver::new(&encrypt(&plaintexts, &expected));
+        let longest = plaintexts.iter().position(|p| p.len() == max_len).unwrap();
+        // Knowing the longest plaintext fixes the whole keystream
+        solver
+            .set_plaintext(longest, 0, &plaintexts[longest])
+            .unwrap();
+        solver.solve(&LetterFrequency::english());
+        assert_eq!(expected, solver.keystream());
+        assert_eq!(plaintexts, solver.plaintexts());
+        assert!(solver.bytes().iter().all(|b| b.locked));
+        // Unlocked bytes are guessed again
+        solver.set_keystream_byte(0, !expected[0]).unwrap();
+        solver.unlock(0);
+        solver.solve(&LetterFrequency::english());
+        assert_eq!(expected[0], solver.keystream()[0]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut solver = KeystreamSolver::new(&[b"abc".to_vec(), b"de".to_vec()]);
+        assert_eq!(
+            Err(KeystreamError::CiphertextOutOfRange { index: 2, count: 2 }),
+            solver.set_plaintext(2, 0, "a")
+        );
+        assert_eq!(
+            Err(KeystreamError::OffsetOutOfRange { offset: 1, len: 2 }),
+            solver.set_plaintext(1, 1, "xy")
+        );
+        assert_eq!(
+            Err(KeystreamError::OffsetOutOfRange {
+                offset: usize::MAX,
+                len: 2
+            }),
+            solver.set_plaintext(1, usize::MAX, "x")
+        );
+        assert_eq!(
+            Err(KeystreamError::OffsetOutOfRange { offset: 3, len: 3 }),
+            solver.set_keystream_byte(3, 0)
+        );
+        assert_eq!(None, solver.plaintext(2));
+        let mut empty = KeystreamSolver::new::<&[u8]>(&[]);
+        empty.solve(&LetterFrequency::english());
+        assert!(empty.keystream().is_empty());
+    }
+}